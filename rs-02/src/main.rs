@@ -2,11 +2,29 @@ use std::fs;
 use std::io;
 use std::str::FromStr;
 
+use model::Model;
+
+mod model;
+
 fn main() {
-    println!("part 1: {}", run(&read_input(), Position1::new()));
+    println!(
+        "part 1: {}",
+        run(&read_input(), Position1::<i64>::new()).unwrap()
+    );
     println!("part 1 mini: {:?}", part_1_mini());
-    println!("part 2: {}", run(&read_input(), Position2::new()));
+    println!(
+        "part 2: {}",
+        run(&read_input(), Position2::<i64>::new()).unwrap()
+    );
     println!("part 2 mini: {:?}", part_2_mini());
+    for filename in ["part_1.model", "part_2.model"] {
+        let model = read_model(filename);
+        println!(
+            "{}: {}",
+            filename,
+            run(&read_input(), model.start::<i64>()).unwrap()
+        );
+    }
 }
 
 /// smaller versions of the solution, based on some ideas from reddit
@@ -49,14 +67,29 @@ fn read_input() -> String {
     fs::read_to_string("input").unwrap()
 }
 
-fn run(input: &str, initial_position: impl Position) -> i32 {
+fn read_model(filename: &str) -> Model {
+    fs::read_to_string(filename).unwrap().parse().unwrap()
+}
+
+/// apply every motion in order, failing with the index of the first command that overflows
+fn run<N: Number>(input: &str, initial_position: impl Position<N>) -> Result<N, Overflow> {
     input
         .lines()
         .map(|line| line.trim().parse::<Motion>().unwrap())
-        .fold(initial_position, |position, motion| {
-            position.update(&motion)
-        })
+        .enumerate()
+        .try_fold(initial_position, |position, (command, motion)| {
+            position.update(&motion).ok_or(Overflow::Command(command))
+        })?
         .calc_multiple()
+        .ok_or(Overflow::Multiple)
+}
+
+#[derive(Debug, PartialEq)]
+enum Overflow {
+    /// the command at this (zero based) index pushed a value out of range
+    Command(usize),
+    /// every command fit, but the final result did not
+    Multiple,
 }
 
 #[derive(Debug, PartialEq)]
@@ -65,6 +98,20 @@ enum Motion {
     Down(i32),
     Forward(i32),
 }
+impl Motion {
+    fn command(&self) -> &'static str {
+        match self {
+            Motion::Up(_) => "up",
+            Motion::Down(_) => "down",
+            Motion::Forward(_) => "forward",
+        }
+    }
+    fn amount(&self) -> i32 {
+        match *self {
+            Motion::Up(x) | Motion::Down(x) | Motion::Forward(x) => x,
+        }
+    }
+}
 impl FromStr for Motion {
     type Err = io::Error;
 
@@ -78,76 +125,104 @@ impl FromStr for Motion {
     }
 }
 
-trait Position {
-    fn new() -> Self;
-    fn update(self, motion: &Motion) -> Self;
-    fn calc_multiple(self) -> i32;
+/// numeric types a position can be tracked in, all arithmetic is checked so
+/// overflow is reported instead of silently wrapping
+trait Number: Copy + From<i32> {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(impl Number for $t {
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+        })*
+    };
+}
+impl_number!(i32, i64, i128);
+
+trait Position<N: Number>: Sized {
+    fn update(self, motion: &Motion) -> Option<Self>;
+    fn calc_multiple(self) -> Option<N>;
 }
 
-struct Position1 {
-    horizontal: i32,
-    depth: i32,
+struct Position1<N> {
+    horizontal: N,
+    depth: N,
 }
-impl Position for Position1 {
+impl<N: Number> Position1<N> {
     fn new() -> Self {
         Self {
-            horizontal: 0,
-            depth: 0,
+            horizontal: N::from(0),
+            depth: N::from(0),
         }
     }
-    fn update(mut self, motion: &Motion) -> Self {
-        match motion {
+}
+impl<N: Number> Position<N> for Position1<N> {
+    fn update(mut self, motion: &Motion) -> Option<Self> {
+        match *motion {
             Motion::Forward(x) => {
-                self.horizontal += x;
-                self
+                self.horizontal = self.horizontal.checked_add(x.into())?;
+                Some(self)
             }
             Motion::Up(x) => {
-                self.depth -= x;
-                self
+                self.depth = self.depth.checked_sub(x.into())?;
+                Some(self)
             }
             Motion::Down(x) => {
-                self.depth += x;
-                self
+                self.depth = self.depth.checked_add(x.into())?;
+                Some(self)
             }
         }
     }
-    fn calc_multiple(self) -> i32 {
-        self.horizontal * self.depth
+    fn calc_multiple(self) -> Option<N> {
+        self.horizontal.checked_mul(self.depth)
     }
 }
 
-struct Position2 {
-    aim: i32,
-    horizontal: i32,
-    depth: i32,
+struct Position2<N> {
+    aim: N,
+    horizontal: N,
+    depth: N,
 }
-impl Position for Position2 {
+impl<N: Number> Position2<N> {
     fn new() -> Self {
         Self {
-            aim: 0,
-            horizontal: 0,
-            depth: 0,
+            aim: N::from(0),
+            horizontal: N::from(0),
+            depth: N::from(0),
         }
     }
-    fn update(mut self, motion: &Motion) -> Self {
-        match motion {
+}
+impl<N: Number> Position<N> for Position2<N> {
+    fn update(mut self, motion: &Motion) -> Option<Self> {
+        match *motion {
             Motion::Forward(x) => {
-                self.horizontal += x;
-                self.depth += self.aim * x;
-                self
+                self.horizontal = self.horizontal.checked_add(x.into())?;
+                self.depth = self.depth.checked_add(self.aim.checked_mul(x.into())?)?;
+                Some(self)
             }
             Motion::Up(x) => {
-                self.aim -= x;
-                self
+                self.aim = self.aim.checked_sub(x.into())?;
+                Some(self)
             }
             Motion::Down(x) => {
-                self.aim += x;
-                self
+                self.aim = self.aim.checked_add(x.into())?;
+                Some(self)
             }
         }
     }
-    fn calc_multiple(self) -> i32 {
-        self.horizontal * self.depth
+    fn calc_multiple(self) -> Option<N> {
+        self.horizontal.checked_mul(self.depth)
     }
 }
 
@@ -164,7 +239,7 @@ mod tests {
 
     #[test]
     fn test_parse_input() {
-        vec![
+        [
             ("forward 1", Motion::Forward(1)),
             ("up 2", Motion::Up(2)),
             ("down 3", Motion::Down(3)),
@@ -174,17 +249,17 @@ mod tests {
             assert_eq!(input.parse::<Motion>().unwrap(), *expected);
         });
 
-        assert!(matches!("foobar 4".parse::<Motion>(), Err(_)));
+        assert!("foobar 4".parse::<Motion>().is_err());
     }
 
     #[test]
     fn test_part_1_sample() {
-        assert_eq!(run(&SAMPLE_INPUT, Position1::new()), 150);
+        assert_eq!(run(SAMPLE_INPUT, Position1::<i32>::new()), Ok(150));
     }
 
     #[test]
     fn test_part_1_real() {
-        assert_eq!(run(&read_input(), Position1::new()), 2272262);
+        assert_eq!(run(&read_input(), Position1::<i64>::new()), Ok(2272262));
     }
 
     #[test]
@@ -194,16 +269,46 @@ mod tests {
 
     #[test]
     fn test_part_2_sample() {
-        assert_eq!(run(&SAMPLE_INPUT, Position2::new()), 900);
+        assert_eq!(run(SAMPLE_INPUT, Position2::<i32>::new()), Ok(900));
     }
 
     #[test]
     fn test_part_2_real() {
-        assert_eq!(run(&read_input(), Position2::new()), 2134882034);
+        assert_eq!(run(&read_input(), Position2::<i64>::new()), Ok(2134882034));
     }
 
     #[test]
     fn test_part_2_mini() {
         assert_eq!(part_2_mini(), 2134882034);
     }
+
+    #[test]
+    fn test_models_match_structs() {
+        let part_1 = read_model("part_1.model");
+        let part_2 = read_model("part_2.model");
+        assert_eq!(run(SAMPLE_INPUT, part_1.start::<i32>()), Ok(150));
+        assert_eq!(run(SAMPLE_INPUT, part_2.start::<i32>()), Ok(900));
+        assert_eq!(run(&read_input(), part_1.start::<i64>()), Ok(2272262));
+        assert_eq!(run(&read_input(), part_2.start::<i64>()), Ok(2134882034));
+    }
+
+    #[test]
+    fn test_wider_types_agree() {
+        assert_eq!(run(&read_input(), Position2::<i32>::new()), Ok(2134882034));
+        assert_eq!(run(&read_input(), Position2::<i128>::new()), Ok(2134882034));
+    }
+
+    #[test]
+    fn test_overflow_reports_command() {
+        let input = "down 2000000000\nforward 2\nforward 1";
+        assert_eq!(
+            run(input, Position2::<i32>::new()),
+            Err(Overflow::Command(1))
+        );
+        assert_eq!(run(input, Position2::<i64>::new()), Ok(3 * 6000000000));
+        assert_eq!(
+            run("forward 70000\ndown 70000", Position1::<i32>::new()),
+            Err(Overflow::Multiple)
+        );
+    }
 }
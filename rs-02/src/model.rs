@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{Motion, Number, Position};
+
+const COMMANDS: [&str; 3] = ["up", "down", "forward"];
+
+/// A position model described by data rather than a hand written struct.
+///
+/// The config format is line based, blank lines and `#` comments are ignored:
+///
+/// ```text
+/// state aim horizontal depth
+/// forward: horizontal += x, depth += aim * x
+/// up: aim -= x
+/// down: aim += x
+/// result: horizontal * depth
+/// ```
+///
+/// Every state variable starts at zero. Each command lists the updates to
+/// apply in order, an update is `<var> (+=|-=|=) <expr>` where `<expr>` is a
+/// product of state variables, integer literals and `x` (the command amount).
+#[derive(Debug)]
+pub struct Model {
+    vars: Vec<String>,
+    rules: HashMap<String, Vec<Update>>,
+    result: Vec<Factor>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Set,
+}
+
+#[derive(Debug, PartialEq)]
+enum Factor {
+    Var(usize),
+    Amount,
+    Const(i32),
+}
+
+#[derive(Debug)]
+struct Update {
+    target: usize,
+    op: Op,
+    expr: Vec<Factor>,
+}
+
+impl Model {
+    pub fn start<N: Number>(&self) -> ModelPosition<'_, N> {
+        ModelPosition {
+            model: self,
+            values: vec![N::from(0); self.vars.len()],
+        }
+    }
+
+    fn var_index(&self, name: &str) -> Result<usize, String> {
+        self.vars
+            .iter()
+            .position(|var| var == name)
+            .ok_or(format!("Unknown state variable \"{}\"", name))
+    }
+
+    fn parse_expr(&self, input: &str) -> Result<Vec<Factor>, String> {
+        input
+            .split('*')
+            .map(|factor| match factor.trim() {
+                "x" => Ok(Factor::Amount),
+                "" => Err(format!("Missing factor in \"{}\"", input.trim())),
+                name => match name.parse() {
+                    Ok(value) => Ok(Factor::Const(value)),
+                    Err(_) => self.var_index(name).map(Factor::Var),
+                },
+            })
+            .collect()
+    }
+
+    fn parse_update(&self, input: &str) -> Result<Update, String> {
+        let input = input.trim();
+        let (target, op, expr) = [("+=", Op::Add), ("-=", Op::Sub), ("=", Op::Set)]
+            .into_iter()
+            .find_map(|(token, op)| {
+                input
+                    .split_once(token)
+                    .map(|(target, expr)| (target, op, expr))
+            })
+            .ok_or(format!("Expected an assignment in \"{}\"", input))?;
+        Ok(Update {
+            target: self.var_index(target.trim())?,
+            op,
+            expr: self.parse_expr(expr)?,
+        })
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut lines = input
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let vars: Vec<String> = match lines.next().and_then(|line| line.strip_prefix("state ")) {
+            Some(vars) => vars.split_whitespace().map(String::from).collect(),
+            None => return Err("Model must start with a state line".to_string()),
+        };
+        if let Some(var) = vars.iter().find(|var| *var == "x") {
+            return Err(format!("\"{}\" is reserved for the command amount", var));
+        }
+        for (i, var) in vars.iter().enumerate() {
+            if vars[..i].contains(var) {
+                return Err(format!("State variable \"{}\" is listed twice", var));
+            }
+        }
+
+        let mut model = Model {
+            vars,
+            rules: HashMap::new(),
+            result: vec![],
+        };
+        let mut has_result = false;
+        for line in lines {
+            let (key, body) = line
+                .split_once(':')
+                .ok_or(format!("Expected \"<command>: ...\" in \"{}\"", line))?;
+            match key.trim() {
+                "result" if has_result => return Err("Model has two result lines".to_string()),
+                "result" => {
+                    model.result = model.parse_expr(body)?;
+                    has_result = true;
+                }
+                command if model.rules.contains_key(command) => {
+                    return Err(format!("Command \"{}\" is defined twice", command))
+                }
+                command if COMMANDS.contains(&command) => {
+                    let updates = body
+                        .split(',')
+                        .map(|update| model.parse_update(update))
+                        .collect::<Result<_, _>>()?;
+                    model.rules.insert(command.to_string(), updates);
+                }
+                other => return Err(format!("Unknown command \"{}\"", other)),
+            }
+        }
+        if !has_result {
+            return Err("Model is missing a result line".to_string());
+        }
+        Ok(model)
+    }
+}
+
+/// the running state of a `Model`, one value per state variable
+pub struct ModelPosition<'a, N> {
+    model: &'a Model,
+    values: Vec<N>,
+}
+
+impl<N: Number> ModelPosition<'_, N> {
+    fn eval(&self, expr: &[Factor], amount: i32) -> Option<N> {
+        expr.iter().try_fold(N::from(1), |product, factor| {
+            let value = match *factor {
+                Factor::Var(index) => self.values[index],
+                Factor::Amount => N::from(amount),
+                Factor::Const(value) => N::from(value),
+            };
+            product.checked_mul(value)
+        })
+    }
+}
+
+impl<N: Number> Position<N> for ModelPosition<'_, N> {
+    fn update(mut self, motion: &Motion) -> Option<Self> {
+        let updates = match self.model.rules.get(motion.command()) {
+            Some(updates) => updates,
+            None => return Some(self),
+        };
+        for update in updates {
+            let value = self.eval(&update.expr, motion.amount())?;
+            let current = self.values[update.target];
+            self.values[update.target] = match update.op {
+                Op::Add => current.checked_add(value)?,
+                Op::Sub => current.checked_sub(value)?,
+                Op::Set => value,
+            };
+        }
+        Some(self)
+    }
+    fn calc_multiple(self) -> Option<N> {
+        self.eval(&self.model.result, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_model() {
+        let model: Model = "state a b\nup: a += 2 * x, b = a * b\nresult: a"
+            .parse()
+            .unwrap();
+        assert_eq!(model.vars, vec!["a", "b"]);
+        let updates = &model.rules["up"];
+        assert_eq!(updates[0].target, 0);
+        assert_eq!(updates[0].op, Op::Add);
+        assert_eq!(updates[0].expr, vec![Factor::Const(2), Factor::Amount]);
+        assert_eq!(updates[1].op, Op::Set);
+        assert_eq!(updates[1].expr, vec![Factor::Var(0), Factor::Var(1)]);
+        assert_eq!(model.result, vec![Factor::Var(0)]);
+    }
+
+    #[test]
+    fn test_parse_model_errors() {
+        [
+            "up: a += x\nresult: a",
+            "state a\nup: b += x\nresult: a",
+            "state a\nsideways: a += x\nresult: a",
+            "state a\nup: a + x\nresult: a",
+            "state a\nup: a += x",
+            "state x\nresult: x",
+        ]
+        .iter()
+        .for_each(|input| {
+            assert!(input.parse::<Model>().is_err(), "{}", input);
+        });
+    }
+
+    #[test]
+    fn test_duplicate_definitions() {
+        assert_eq!(
+            "state a b a\nresult: a".parse::<Model>().unwrap_err(),
+            "State variable \"a\" is listed twice"
+        );
+        assert_eq!(
+            "state a\nup: a += x\nup: a -= x\nresult: a"
+                .parse::<Model>()
+                .unwrap_err(),
+            "Command \"up\" is defined twice"
+        );
+        assert!("state a\nresult: a\nresult: 2 * a"
+            .parse::<Model>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_unlisted_command_is_ignored() {
+        let model: Model = "state depth\ndown: depth += x\nresult: depth"
+            .parse()
+            .unwrap();
+        let position = model
+            .start::<i32>()
+            .update(&Motion::Down(3))
+            .unwrap()
+            .update(&Motion::Forward(5))
+            .unwrap();
+        assert_eq!(position.calc_multiple(), Some(3));
+    }
+}
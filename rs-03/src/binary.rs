@@ -1,49 +1,198 @@
 use std::fmt::{Display, Error, Formatter};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
 use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+const WORD_BITS: usize = 64;
+
+/// A fixed width binary number, bits are packed into 64 bit words.
+///
+/// Index 0 is the leftmost (most significant) bit, matching the order the
+/// digits are written in. Internally words are stored least significant
+/// first so conversions to integers are just shifts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Binary {
-    pub bits: Vec<bool>,
+    width: usize,
+    words: Vec<u64>,
 }
 
 impl Binary {
+    /// an all zero number of the given width
+    pub fn zeros(width: usize) -> Self {
+        Self {
+            width,
+            words: vec![0; width.div_ceil(WORD_BITS)],
+        }
+    }
+
+    /// build from bits in written order, most significant first
+    pub fn from_bits(bits: impl IntoIterator<Item = bool>) -> Self {
+        let bits: Vec<bool> = bits.into_iter().collect();
+        let mut binary = Self::zeros(bits.len());
+        for (index, bit) in bits.into_iter().enumerate() {
+            binary.set(index, bit);
+        }
+        binary
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// word and shift of the bit at `index`, counting from the left
+    fn locate(&self, index: usize) -> (usize, usize) {
+        assert!(index < self.width, "bit {} out of range", index);
+        let significance = self.width - 1 - index;
+        (significance / WORD_BITS, significance % WORD_BITS)
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        let (word, shift) = self.locate(index);
+        self.words[word] >> shift & 1 == 1
+    }
+
+    pub fn set(&mut self, index: usize, bit: bool) {
+        let (word, shift) = self.locate(index);
+        if bit {
+            self.words[word] |= 1 << shift;
+        } else {
+            self.words[word] &= !(1 << shift);
+        }
+    }
+
     pub fn value_at(&self, index: usize) -> usize {
-        self.bits[index] as usize
+        self.get(index) as usize
     }
 
-    pub fn to_decimal(&self) -> i32 {
-        let mut result = 0;
-        for (i, bit) in self.bits.iter().rev().enumerate() {
-            if *bit {
-                result += 1 << i;
+    /// bits in written order, most significant first
+    pub fn bits(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.width).map(|index| self.get(index))
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// the value as a u128, or `None` if it doesn't fit
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.words.iter().skip(2).any(|word| *word != 0) {
+            return None;
+        }
+        let low = self.words.first().copied().unwrap_or(0) as u128;
+        let high = self.words.get(1).copied().unwrap_or(0) as u128;
+        Some(high << WORD_BITS | low)
+    }
+
+    /// the value as a u128, panics if it doesn't fit
+    pub fn to_decimal(&self) -> u128 {
+        self.to_u128()
+            .unwrap_or_else(|| panic!("{} is too wide for a u128", self))
+    }
+
+    /// the value written out in base 10, works for any width
+    pub fn to_decimal_string(&self) -> String {
+        // little endian base 10^9 limbs, doubled and incremented once per bit
+        const BASE: u64 = 1_000_000_000;
+        let mut limbs: Vec<u64> = vec![0];
+        for bit in self.bits() {
+            let mut carry = bit as u64;
+            for limb in limbs.iter_mut() {
+                let value = *limb * 2 + carry;
+                *limb = value % BASE;
+                carry = value / BASE;
             }
+            if carry > 0 {
+                limbs.push(carry);
+            }
+        }
+        let mut result = limbs.last().unwrap().to_string();
+        for limb in limbs.iter().rev().skip(1) {
+            result.push_str(&format!("{:09}", limb));
         }
         result
     }
+
+    /// clear any bits in the top word beyond `width`
+    fn mask_top(&mut self) {
+        let used = self.width % WORD_BITS;
+        if used != 0 {
+            if let Some(top) = self.words.last_mut() {
+                *top &= (1 << used) - 1;
+            }
+        }
+    }
+
+    fn zip_words(mut self, other: &Binary, op: impl Fn(u64, u64) -> u64) -> Binary {
+        assert_eq!(self.width, other.width, "binary widths must match");
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word = op(*word, *other);
+        }
+        self
+    }
+}
+
+impl BitAnd for &Binary {
+    type Output = Binary;
+
+    fn bitand(self, other: &Binary) -> Binary {
+        self.clone().zip_words(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &Binary {
+    type Output = Binary;
+
+    fn bitor(self, other: &Binary) -> Binary {
+        self.clone().zip_words(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &Binary {
+    type Output = Binary;
+
+    fn bitxor(self, other: &Binary) -> Binary {
+        self.clone().zip_words(other, |a, b| a ^ b)
+    }
+}
+
+/// the complement, every bit within `width` flipped
+impl Not for &Binary {
+    type Output = Binary;
+
+    fn not(self) -> Binary {
+        let mut result = self.clone();
+        for word in result.words.iter_mut() {
+            *word = !*word;
+        }
+        result.mask_top();
+        result
+    }
 }
 
 impl FromStr for Binary {
     type Err = String;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        Ok(Self {
-            bits: input
-                .chars()
-                .map(|c| match c {
-                    '0' => false,
-                    '1' => true,
-                    _ => panic!("Invalid character"),
-                })
-                .collect(),
-        })
+        let bits = input
+            .chars()
+            .enumerate()
+            .map(|(index, c)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(format!("Invalid character {:?} at {}", c, index)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_bits(bits))
     }
 }
 
 impl Display for Binary {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let mut result = String::with_capacity(self.bits.len());
-        for bit in self.bits.iter() {
-            result.push_str(if *bit { "1" } else { "0" });
+        let mut result = String::with_capacity(self.width);
+        for bit in self.bits() {
+            result.push_str(if bit { "1" } else { "0" });
         }
         write!(f, "{}", result)
     }
@@ -59,6 +208,14 @@ mod tests {
         assert_eq!(binary.to_decimal(), 255);
     }
 
+    #[test]
+    fn test_from_str_invalid() {
+        assert_eq!(
+            Binary::from_str("0120"),
+            Err("Invalid character '2' at 2".to_string())
+        );
+    }
+
     #[test]
     fn test_binary_value_at() {
         let binary: Binary = "001011".parse().unwrap();
@@ -81,4 +238,41 @@ mod tests {
         let binary: Binary = "001111".parse().unwrap();
         assert_eq!(format!("{}", binary), "001111");
     }
+
+    #[test]
+    fn test_wide_binary() {
+        let input = format!("1{}", "0".repeat(127));
+        let binary: Binary = input.parse().unwrap();
+        assert_eq!(binary.to_u128(), Some(1 << 127));
+        assert_eq!(format!("{}", binary), input);
+
+        let wider: Binary = format!("1{}", "0".repeat(128)).parse().unwrap();
+        assert_eq!(wider.to_u128(), None);
+        assert_eq!(
+            wider.to_decimal_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(Binary::zeros(70).to_decimal_string(), "0");
+    }
+
+    #[test]
+    fn test_bitwise_ops() {
+        let a: Binary = "1100".parse().unwrap();
+        let b: Binary = "1010".parse().unwrap();
+        assert_eq!(format!("{}", &a & &b), "1000");
+        assert_eq!(format!("{}", &a | &b), "1110");
+        assert_eq!(format!("{}", &a ^ &b), "0110");
+        assert_eq!(format!("{}", !&a), "0011");
+    }
+
+    #[test]
+    fn test_complement_masks_width() {
+        let binary: Binary = format!("1{}", "0".repeat(69)).parse().unwrap();
+        let complement = !&binary;
+        assert_eq!(complement.count_ones(), 69);
+        assert_eq!(
+            (&binary | &complement).to_decimal_string(),
+            ((1u128 << 70) - 1).to_string()
+        );
+    }
 }
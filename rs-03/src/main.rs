@@ -1,86 +1,176 @@
+use std::env;
 use std::fs;
 
 use binary::Binary;
+use report::Report;
+use tie::{Step, Tie, TieBreak, Ties};
+use trie::BitTrie;
 
 mod binary;
+mod report;
+mod tie;
+mod trie;
 
 fn main() {
     println!("part 1: {}", part_1("input"));
     println!("part 2: {}", part_2("input"));
+    println!("part 2 trie: {}", part_2_trie("input"));
+
+    // `cargo run -- [policy]` or `cargo run -- report <text|csv> [policy]`
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (format, policy) = match args.first().map(String::as_str) {
+        Some("report") => (args.get(1).map(String::as_str), args.get(2)),
+        _ => (None, args.first()),
+    };
+    let policy: TieBreak = policy.map_or(TieBreak::PreferOne, |arg| arg.parse().unwrap());
+    let diagnosis = match diagnose(&read_codes("input"), policy) {
+        Ok(diagnosis) => diagnosis,
+        Err(tie) => {
+            println!("tie at column {} with {:?}", tie.position, policy);
+            return;
+        }
+    };
+    match format {
+        None => print!("{}", diagnosis.tie_report()),
+        Some("text") => print!("{}", Report::new(&diagnosis).to_text()),
+        Some("csv") => print!("{}", Report::new(&diagnosis).to_csv()),
+        Some(other) => println!("unknown report format \"{}\"", other),
+    }
 }
 
-fn count_ones(codes: &[Binary]) -> Vec<i32> {
-    let binary_width = codes[0].bits.len();
+fn read_codes(filename: &str) -> Vec<Binary> {
+    fs::read_to_string(filename)
+        .unwrap()
+        .lines()
+        .map(|line| line.parse().unwrap())
+        .collect()
+}
+
+fn count_ones(codes: &[Binary]) -> Vec<usize> {
+    let binary_width = codes[0].width();
     codes
         .iter()
         .fold(vec![0; binary_width], |mut counts, code| {
-            for bit_idx in 0..binary_width {
-                if code.value_at(bit_idx) == 1 {
-                    counts[bit_idx] += 1;
+            for (bit_idx, count) in counts.iter_mut().enumerate() {
+                if code.get(bit_idx) {
+                    *count += 1;
                 }
             }
             counts
         })
 }
 
-fn part_1(filename: &str) -> i32 {
-    let codes: Vec<Binary> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .collect();
-    let total_count = codes.len() as i32;
-
-    let counts = count_ones(&codes);
-
-    let gamma = Binary {
-        bits: counts
-            .iter()
-            .map(|count| *count > total_count / 2)
-            .collect(),
-    };
-    let epsilon = Binary {
-        bits: counts
-            .iter()
-            .map(|count| *count < total_count / 2)
-            .collect(),
-    };
-
-    gamma.to_decimal() * epsilon.to_decimal()
-}
-
+#[derive(Clone, Copy)]
 enum Common {
     Most,
     Least,
 }
 
-fn filter_most_common_recursive(common: Common, codes: Vec<Binary>, at_position: usize) -> Binary {
+fn filter_most_common_recursive(
+    common: Common,
+    ties: &mut Ties,
+    codes: Vec<Binary>,
+    at_position: usize,
+) -> Result<Binary, Tie> {
     if codes.len() == 1 {
-        return codes[0].clone();
+        return Ok(codes[0].clone());
     }
-    let counts = count_ones(&codes);
-    let most_common = (counts[at_position] as f64) >= (codes.len() as f64 / 2.0);
-    let to_keep = match common {
-        Common::Most => most_common,
-        Common::Least => !most_common,
-    };
+    let ones = count_ones(&codes)[at_position];
+    let to_keep = ties.keep(common, at_position, ones, codes.len() - ones)?;
     let filtered_codes = codes
         .iter()
-        .filter(|code| code.bits[at_position] == to_keep)
+        .filter(|code| code.get(at_position) == to_keep)
         .cloned()
         .collect();
-    filter_most_common_recursive(common, filtered_codes, at_position + 1)
+    filter_most_common_recursive(common, ties, filtered_codes, at_position + 1)
 }
 
-fn part_2(filename: &str) -> i32 {
-    let codes: Vec<Binary> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.parse().unwrap())
-        .collect();
+/// every rating the diagnostic report produces, along with where ties were broken
+struct Diagnosis {
+    gamma: Binary,
+    epsilon: Binary,
+    oxygen_generator: Binary,
+    co2_scrubber: Binary,
+    gamma_ties: Vec<Tie>,
+    oxygen_generator_ties: Vec<Tie>,
+    co2_scrubber_ties: Vec<Tie>,
+    gamma_steps: Vec<Step>,
+    oxygen_generator_steps: Vec<Step>,
+    co2_scrubber_steps: Vec<Step>,
+}
+
+/// run every rating under the same tie break policy, `TieBreak::Error` fails on the first tie
+fn diagnose(codes: &[Binary], policy: TieBreak) -> Result<Diagnosis, Tie> {
+    let mut gamma_ties = Ties::new(policy);
+    let gamma = Binary::from_bits(
+        count_ones(codes)
+            .into_iter()
+            .enumerate()
+            .map(|(position, ones)| {
+                gamma_ties.keep(Common::Most, position, ones, codes.len() - ones)
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+    // least common is always the opposite of most common, ties included
+    let epsilon = !&gamma;
 
-    let oxygen_generator_rating = filter_most_common_recursive(Common::Most, codes.clone(), 0);
-    let co2_scrubber_rating = filter_most_common_recursive(Common::Least, codes, 0);
+    let mut oxygen_generator_ties = Ties::new(policy);
+    let oxygen_generator =
+        filter_most_common_recursive(Common::Most, &mut oxygen_generator_ties, codes.to_vec(), 0)?;
+    let mut co2_scrubber_ties = Ties::new(policy);
+    let co2_scrubber =
+        filter_most_common_recursive(Common::Least, &mut co2_scrubber_ties, codes.to_vec(), 0)?;
+
+    Ok(Diagnosis {
+        gamma,
+        epsilon,
+        oxygen_generator,
+        co2_scrubber,
+        gamma_ties: gamma_ties.columns,
+        oxygen_generator_ties: oxygen_generator_ties.columns,
+        co2_scrubber_ties: co2_scrubber_ties.columns,
+        gamma_steps: gamma_ties.steps,
+        oxygen_generator_steps: oxygen_generator_ties.steps,
+        co2_scrubber_steps: co2_scrubber_ties.steps,
+    })
+}
+
+impl Diagnosis {
+    fn tie_report(&self) -> String {
+        let mut report = String::new();
+        for (name, ties) in [
+            ("gamma/epsilon", &self.gamma_ties),
+            ("oxygen generator", &self.oxygen_generator_ties),
+            ("co2 scrubber", &self.co2_scrubber_ties),
+        ] {
+            for tie in ties {
+                report.push_str(&format!(
+                    "{}: tie at column {} ({} ones, {} zeros)\n",
+                    name, tie.position, tie.count, tie.count
+                ));
+            }
+        }
+        report
+    }
+}
+
+fn part_1(filename: &str) -> u128 {
+    let diagnosis = diagnose(&read_codes(filename), TieBreak::PreferOne).unwrap();
+    diagnosis.gamma.to_decimal() * diagnosis.epsilon.to_decimal()
+}
+
+fn part_2(filename: &str) -> u128 {
+    let diagnosis = diagnose(&read_codes(filename), TieBreak::PreferOne).unwrap();
+    diagnosis.oxygen_generator.to_decimal() * diagnosis.co2_scrubber.to_decimal()
+}
+
+/// same answer as `part_2`, but built once into a trie instead of refiltering
+fn part_2_trie(filename: &str) -> u128 {
+    let trie = BitTrie::new(&read_codes(filename));
+    let mut ties = Ties::new(TieBreak::PreferOne);
+
+    let oxygen_generator_rating = trie.rating(Common::Most, &mut ties).unwrap();
+    let co2_scrubber_rating = trie.rating(Common::Least, &mut ties).unwrap();
 
     oxygen_generator_rating.to_decimal() * co2_scrubber_rating.to_decimal()
 }
@@ -108,4 +198,83 @@ mod tests {
     fn test_part_2_full() {
         assert_eq!(part_2("input"), 4996233);
     }
+
+    #[test]
+    fn test_trie_matches_recursive() {
+        for filename in ["input.test", "input"] {
+            let codes = read_codes(filename);
+            let trie = BitTrie::new(&codes);
+            for policy in [TieBreak::PreferOne, TieBreak::PreferZero] {
+                for common in [Common::Most, Common::Least] {
+                    let mut trie_ties = Ties::new(policy);
+                    let mut recursive_ties = Ties::new(policy);
+                    assert_eq!(
+                        trie.rating(common, &mut trie_ties),
+                        filter_most_common_recursive(common, &mut recursive_ties, codes.clone(), 0)
+                    );
+                    assert_eq!(trie_ties.columns, recursive_ties.columns);
+                }
+            }
+        }
+        assert_eq!(part_2_trie("input"), 4996233);
+    }
+
+    #[test]
+    fn test_tie_break_policies() {
+        let codes = read_codes("input.test");
+
+        let diagnosis = diagnose(&codes, TieBreak::PreferOne).unwrap();
+        assert!(diagnosis.gamma_ties.is_empty());
+        assert_eq!(
+            diagnosis.oxygen_generator_ties,
+            vec![Tie {
+                position: 4,
+                count: 1
+            }]
+        );
+        assert_eq!(diagnosis.oxygen_generator.to_decimal(), 23);
+        assert_eq!(diagnosis.co2_scrubber.to_decimal(), 10);
+        assert_eq!(
+            diagnosis.tie_report(),
+            "oxygen generator: tie at column 4 (1 ones, 1 zeros)\n\
+             co2 scrubber: tie at column 2 (1 ones, 1 zeros)\n"
+        );
+
+        let diagnosis = diagnose(&codes, TieBreak::PreferZero).unwrap();
+        assert_eq!(diagnosis.oxygen_generator.to_decimal(), 22);
+
+        assert!(diagnose(&codes, TieBreak::Error).is_err());
+    }
+
+    #[test]
+    fn test_filter_steps() {
+        let diagnosis = diagnose(&read_codes("input.test"), TieBreak::PreferOne).unwrap();
+        let survivors =
+            |steps: &[Step]| -> Vec<usize> { steps.iter().map(|step| step.survivors()).collect() };
+        assert_eq!(
+            survivors(&diagnosis.oxygen_generator_steps),
+            vec![7, 4, 3, 2, 1]
+        );
+        assert_eq!(survivors(&diagnosis.co2_scrubber_steps), vec![5, 2, 1]);
+        assert_eq!(diagnosis.gamma_steps.len(), 5);
+    }
+
+    #[test]
+    fn test_gamma_tie_is_consistent() {
+        let codes: Vec<Binary> = ["10", "01", "11", "00"]
+            .iter()
+            .map(|code| code.parse().unwrap())
+            .collect();
+        let diagnosis = diagnose(&codes, TieBreak::PreferOne).unwrap();
+        assert_eq!(format!("{}", diagnosis.gamma), "11");
+        assert_eq!(format!("{}", diagnosis.epsilon), "00");
+        assert_eq!(diagnosis.gamma_ties.len(), 2);
+        assert_eq!(
+            diagnose(&codes, TieBreak::Error).err(),
+            Some(Tie {
+                position: 0,
+                count: 2
+            })
+        );
+    }
 }
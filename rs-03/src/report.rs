@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+
+use crate::tie::Step;
+use crate::Diagnosis;
+
+const HEADER: [&str; 10] = [
+    "rating",
+    "step",
+    "column",
+    "candidates",
+    "ones",
+    "zeros",
+    "majority",
+    "margin",
+    "kept",
+    "survivors",
+];
+
+/// one line of the report, a column decision made while building a rating
+#[derive(Debug, PartialEq)]
+struct Row {
+    rating: &'static str,
+    step: usize,
+    step_data: Step,
+}
+
+impl Row {
+    fn cells(&self) -> [String; 10] {
+        let Step {
+            position,
+            ones,
+            zeros,
+            kept,
+        } = self.step_data;
+        let majority = match ones.cmp(&zeros) {
+            Ordering::Greater => "1",
+            Ordering::Less => "0",
+            Ordering::Equal => "tie",
+        };
+        // gamma is read straight off the counts, nothing is filtered out
+        let survivors = match self.rating {
+            "gamma" => String::new(),
+            _ => self.step_data.survivors().to_string(),
+        };
+        [
+            self.rating.to_string(),
+            self.step.to_string(),
+            position.to_string(),
+            self.step_data.candidates().to_string(),
+            ones.to_string(),
+            zeros.to_string(),
+            majority.to_string(),
+            ones.abs_diff(zeros).to_string(),
+            (kept as u8).to_string(),
+            survivors,
+        ]
+    }
+}
+
+/// Per column statistics behind a `Diagnosis`: the ones/zeros split of every
+/// column across all codes (which decides gamma), then every step of the
+/// oxygen generator and co2 scrubber filters with how many candidates survived.
+pub struct Report {
+    rows: Vec<Row>,
+}
+
+impl Report {
+    pub fn new(diagnosis: &Diagnosis) -> Self {
+        let mut rows = vec![];
+        for (rating, steps) in [
+            ("gamma", &diagnosis.gamma_steps),
+            ("oxygen generator", &diagnosis.oxygen_generator_steps),
+            ("co2 scrubber", &diagnosis.co2_scrubber_steps),
+        ] {
+            rows.extend(steps.iter().enumerate().map(|(step, step_data)| Row {
+                rating,
+                step,
+                step_data: *step_data,
+            }));
+        }
+        Self { rows }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut result = HEADER.join(",");
+        result.push('\n');
+        for row in self.rows.iter() {
+            result.push_str(&row.cells().join(","));
+            result.push('\n');
+        }
+        result
+    }
+
+    /// the same table as `to_csv`, with columns padded to line up
+    pub fn to_text(&self) -> String {
+        let cells: Vec<[String; 10]> = self.rows.iter().map(|row| row.cells()).collect();
+        let widths: Vec<usize> = (0..HEADER.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|row| row[i].len())
+                    .chain([HEADER[i].len()])
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+        let format_line = |line: Vec<&str>| -> String {
+            let padded: Vec<String> = line
+                .iter()
+                .zip(widths.iter())
+                .enumerate()
+                .map(|(i, (cell, width))| match i {
+                    0 => format!("{:<width$}", cell, width = width),
+                    _ => format!("{:>width$}", cell, width = width),
+                })
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+
+        let mut result = format_line(HEADER.to_vec());
+        for row in cells.iter() {
+            result.push_str(&format_line(row.iter().map(String::as_str).collect()));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tie::TieBreak;
+    use crate::{diagnose, read_codes};
+
+    #[test]
+    fn test_csv() {
+        let diagnosis = diagnose(&read_codes("input.test"), TieBreak::PreferOne).unwrap();
+        let csv = Report::new(&diagnosis).to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "rating,step,column,candidates,ones,zeros,majority,margin,kept,survivors"
+        );
+        assert_eq!(lines[1], "gamma,0,0,12,7,5,1,2,1,");
+        assert_eq!(lines[10], "oxygen generator,4,4,2,1,1,tie,0,1,1");
+        assert_eq!(lines[13], "co2 scrubber,2,2,2,1,1,tie,0,0,1");
+        assert_eq!(lines.len(), 14);
+    }
+
+    #[test]
+    fn test_text() {
+        let diagnosis = diagnose(&read_codes("input.test"), TieBreak::PreferOne).unwrap();
+        let text = Report::new(&diagnosis).to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "rating            step  column  candidates  ones  zeros  majority  margin  kept  survivors"
+        );
+        assert_eq!(
+            lines[1],
+            "gamma                0       0          12     7      5         1       2     1"
+        );
+    }
+}
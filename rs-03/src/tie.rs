@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::Common;
+
+/// What counts as the most common bit when ones and zeros are level
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TieBreak {
+    PreferOne,
+    PreferZero,
+    Error,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "prefer-one" => Ok(TieBreak::PreferOne),
+            "prefer-zero" => Ok(TieBreak::PreferZero),
+            "error" => Ok(TieBreak::Error),
+            _ => Err(format!("Unknown tie break policy \"{}\"", input)),
+        }
+    }
+}
+
+/// a column where ones and zeros were equally common
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tie {
+    pub position: usize,
+    /// how many ones (and so how many zeros) there were
+    pub count: usize,
+}
+
+/// a single column decision, how many candidates had each bit and which was kept
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub position: usize,
+    pub ones: usize,
+    pub zeros: usize,
+    pub kept: bool,
+}
+
+impl Step {
+    pub fn candidates(&self) -> usize {
+        self.ones + self.zeros
+    }
+
+    /// candidates left once only those with the kept bit remain
+    pub fn survivors(&self) -> usize {
+        if self.kept {
+            self.ones
+        } else {
+            self.zeros
+        }
+    }
+}
+
+/// Applies a `TieBreak` policy to each column decision, remembering every
+/// decision and every column that tied along the way.
+#[derive(Debug)]
+pub struct Ties {
+    policy: TieBreak,
+    pub columns: Vec<Tie>,
+    pub steps: Vec<Step>,
+}
+
+impl Ties {
+    pub fn new(policy: TieBreak) -> Self {
+        Self {
+            policy,
+            columns: vec![],
+            steps: vec![],
+        }
+    }
+
+    /// the bit to keep at `position`, the least common bit is always the
+    /// opposite of the most common one so both agree on how ties break
+    pub fn keep(
+        &mut self,
+        common: Common,
+        position: usize,
+        ones: usize,
+        zeros: usize,
+    ) -> Result<bool, Tie> {
+        let kept = if ones == 0 || zeros == 0 {
+            // only one bit is present, so it's the only one that can be kept
+            ones > 0
+        } else {
+            let most_common = match ones.cmp(&zeros) {
+                Ordering::Greater => true,
+                Ordering::Less => false,
+                Ordering::Equal => {
+                    let tie = Tie {
+                        position,
+                        count: ones,
+                    };
+                    self.columns.push(tie);
+                    match self.policy {
+                        TieBreak::PreferOne => true,
+                        TieBreak::PreferZero => false,
+                        TieBreak::Error => return Err(tie),
+                    }
+                }
+            };
+            match common {
+                Common::Most => most_common,
+                Common::Least => !most_common,
+            }
+        };
+        self.steps.push(Step {
+            position,
+            ones,
+            zeros,
+            kept,
+        });
+        Ok(kept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tie_break() {
+        assert_eq!("prefer-zero".parse(), Ok(TieBreak::PreferZero));
+        assert!("prefer-two".parse::<TieBreak>().is_err());
+    }
+
+    #[test]
+    fn test_keep() {
+        let mut ties = Ties::new(TieBreak::PreferOne);
+        assert_eq!(ties.keep(Common::Most, 0, 3, 2), Ok(true));
+        assert_eq!(ties.keep(Common::Least, 0, 3, 2), Ok(false));
+        assert_eq!(ties.keep(Common::Least, 1, 4, 0), Ok(true));
+        assert!(ties.columns.is_empty());
+
+        assert_eq!(ties.keep(Common::Most, 2, 2, 2), Ok(true));
+        assert_eq!(ties.keep(Common::Least, 3, 2, 2), Ok(false));
+        assert_eq!(ties.steps.len(), 5);
+        assert_eq!(ties.steps[1].survivors(), 2);
+        assert_eq!(ties.steps[2].survivors(), 4);
+        assert_eq!(
+            ties.columns,
+            vec![
+                Tie {
+                    position: 2,
+                    count: 2
+                },
+                Tie {
+                    position: 3,
+                    count: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keep_policies() {
+        let mut ties = Ties::new(TieBreak::PreferZero);
+        assert_eq!(ties.keep(Common::Most, 0, 1, 1), Ok(false));
+        assert_eq!(ties.keep(Common::Least, 0, 1, 1), Ok(true));
+
+        let mut ties = Ties::new(TieBreak::Error);
+        assert_eq!(ties.keep(Common::Most, 0, 2, 1), Ok(true));
+        assert_eq!(
+            ties.keep(Common::Most, 5, 1, 1),
+            Err(Tie {
+                position: 5,
+                count: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::binary::Binary;
+use crate::tie::{Tie, Ties};
+use crate::Common;
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    /// index into `BitTrie::nodes` of the child for a 0 and a 1 bit
+    children: [Option<usize>; 2],
+    /// number of codes passing through this node
+    count: usize,
+}
+
+/// A binary trie over equal width codes, each node knows how many codes sit
+/// beneath it so the rating filters become a single walk from the root.
+#[derive(Debug)]
+pub struct BitTrie {
+    width: usize,
+    nodes: Vec<Node>,
+}
+
+impl BitTrie {
+    pub fn new(codes: &[Binary]) -> Self {
+        let width = codes.first().map_or(0, |code| code.width());
+        let mut trie = Self {
+            width,
+            nodes: vec![Node::default()],
+        };
+        for code in codes {
+            assert_eq!(code.width(), width, "codes must all be the same width");
+            trie.insert(code);
+        }
+        trie
+    }
+
+    fn insert(&mut self, code: &Binary) {
+        let mut node = 0;
+        self.nodes[node].count += 1;
+        for bit in code.bits() {
+            node = match self.nodes[node].children[bit as usize] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit as usize] = Some(child);
+                    child
+                }
+            };
+            self.nodes[node].count += 1;
+        }
+    }
+
+    fn count(&self, child: Option<usize>) -> usize {
+        child.map_or(0, |child| self.nodes[child].count)
+    }
+
+    /// Walk down keeping the most or least common bit at each position, with
+    /// ties decided by `ties` exactly as `filter_most_common_recursive` does.
+    pub fn rating(&self, common: Common, ties: &mut Ties) -> Result<Binary, Tie> {
+        let mut node = 0;
+        let mut bits = Vec::with_capacity(self.width);
+        while bits.len() < self.width {
+            let [zeros, ones] = self.nodes[node].children;
+            let position = bits.len();
+            let bit = if self.nodes[node].count == 1 {
+                // a single survivor is the answer, follow it without tallying
+                zeros.is_none()
+            } else {
+                ties.keep(common, position, self.count(ones), self.count(zeros))?
+            };
+            bits.push(bit);
+            node = self.nodes[node].children[bit as usize].unwrap();
+        }
+        Ok(Binary::from_bits(bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tie::TieBreak;
+
+    fn codes(input: &[&str]) -> Vec<Binary> {
+        input.iter().map(|code| code.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_rating_sample() {
+        let trie = BitTrie::new(&codes(&[
+            "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000",
+            "11001", "00010", "01010",
+        ]));
+        let mut ties = Ties::new(TieBreak::PreferOne);
+        let oxygen = trie.rating(Common::Most, &mut ties).unwrap();
+        let co2 = trie.rating(Common::Least, &mut ties).unwrap();
+        assert_eq!(oxygen.to_decimal(), 23);
+        assert_eq!(co2.to_decimal(), 10);
+    }
+
+    #[test]
+    fn test_counts() {
+        let trie = BitTrie::new(&codes(&["00", "01", "01", "11"]));
+        let [zeros, ones] = trie.nodes[0].children;
+        assert_eq!(trie.nodes[0].count, 4);
+        assert_eq!(trie.count(zeros), 3);
+        assert_eq!(trie.count(ones), 1);
+    }
+}
@@ -1,64 +1,158 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Error, Formatter};
+
+use crate::WinCondition::*;
+
 #[derive(Debug)]
 pub struct Board {
     pub marks: Vec<bool>,
     pub values: Vec<i32>,
     pub width: usize,
+    /// every cell holding a given value
+    cells: HashMap<i32, Vec<usize>>,
+    /// the win condition lines each cell belongs to
+    cell_lines: Vec<Vec<usize>>,
+    /// the cells in each win condition line
+    lines: Vec<Vec<usize>>,
+    /// how many cells of each win condition line are still unmarked
+    line_remaining: Vec<usize>,
+    /// the first line to be completed
+    winning_line: Option<usize>,
 }
 
 impl Board {
     pub fn new(values: Vec<i32>, width: usize) -> Self {
-        Self {
+        let mut cells: HashMap<i32, Vec<usize>> = HashMap::new();
+        for (index, value) in values.iter().enumerate() {
+            cells.entry(*value).or_default().push(index);
+        }
+        let mut board = Self {
             marks: vec![false; values.len()],
+            cell_lines: vec![],
+            lines: vec![],
+            line_remaining: vec![],
+            winning_line: None,
+            cells,
             values,
             width,
-        }
+        };
+        board.set_win_condition(&RowsAndColumns);
+        board
     }
 
-    fn rows(&self) -> Vec<Vec<(i32, bool)>> {
-        let num_rows = self.values.len() / self.width;
-        let mut rows = vec![vec![]; num_rows];
-        for i in 0..num_rows {
-            for j in 0..self.width {
-                let index = i * self.width + j;
-                rows[i].push((self.values[index], self.marks[index]));
+    /// switch to a different set of winning lines, keeping any existing marks
+    pub fn set_win_condition(&mut self, condition: &dyn WinCondition) {
+        let lines: Vec<Vec<usize>> = condition
+            .lines(self.width, self.height())
+            .into_iter()
+            .filter(|line| !line.is_empty())
+            .collect();
+        self.cell_lines = vec![vec![]; self.values.len()];
+        self.line_remaining = vec![0; lines.len()];
+        for (line_idx, line) in lines.iter().enumerate() {
+            for &index in line {
+                self.cell_lines[index].push(line_idx);
+                if !self.marks[index] {
+                    self.line_remaining[line_idx] += 1;
+                }
             }
         }
-        rows
+        self.winning_line = self
+            .line_remaining
+            .iter()
+            .position(|&remaining| remaining == 0);
+        self.lines = lines;
+    }
+
+    fn height(&self) -> usize {
+        self.values.len() / self.width
+    }
+
+    fn rows(&self) -> Vec<Vec<(i32, bool)>> {
+        self.values
+            .chunks(self.width)
+            .zip(self.marks.chunks(self.width))
+            .map(|(values, marks)| values.iter().copied().zip(marks.iter().copied()).collect())
+            .collect()
     }
 
+    #[cfg(test)]
     fn columns(&self) -> Vec<Vec<(i32, bool)>> {
-        let num_cols = self.width;
-        let num_rows = self.values.len() / self.width;
-        let mut cols = vec![vec![]; num_cols];
-        for i in 0..num_cols {
-            for j in 0..num_rows {
-                let index = i + j * num_cols;
-                cols[i].push((self.values[index], self.marks[index]));
-            }
-        }
-        cols
+        (0..self.width)
+            .map(|i| {
+                (0..self.height())
+                    .map(|j| {
+                        let index = i + j * self.width;
+                        (self.values[index], self.marks[index])
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
-    pub fn mark(&mut self, draw: i32) {
-        for i in 0..self.values.len() {
-            if self.values[i] == draw {
-                self.marks[i] = true;
+    /// mark every cell holding `draw`, only touching those cells and the
+    /// counters of the lines they sit on. Returns whether anything new was marked.
+    pub fn mark(&mut self, draw: i32) -> bool {
+        let cells = match self.cells.get(&draw) {
+            Some(cells) => cells,
+            None => return false,
+        };
+        let mut marked = false;
+        for &index in cells {
+            if self.marks[index] {
+                continue;
+            }
+            self.marks[index] = true;
+            marked = true;
+            for &line_idx in &self.cell_lines[index] {
+                self.line_remaining[line_idx] -= 1;
+                if self.line_remaining[line_idx] == 0 && self.winning_line.is_none() {
+                    self.winning_line = Some(line_idx);
+                }
             }
         }
+        marked
     }
 
     pub fn has_won(&self) -> bool {
-        for row in self.rows() {
-            if row.iter().all(|&(_, m)| m) {
-                return true;
-            }
-        }
-        for col in self.columns() {
-            if col.iter().all(|&(_, m)| m) {
-                return true;
-            }
-        }
-        false
+        self.winning_line.is_some()
+    }
+
+    /// the cells of the first line to be completed, if any
+    pub fn winning_line(&self) -> Option<&[usize]> {
+        self.winning_line
+            .map(|line_idx| self.lines[line_idx].as_slice())
+    }
+
+    /// Without marking anything, the turn this board wins on and the line it
+    /// completes, given the turn each number is first drawn. A line completes
+    /// on the latest turn of its cells, the board on the earliest of its lines.
+    pub fn winning_turn(&self, draw_turns: &HashMap<i32, usize>) -> Option<(usize, &[usize])> {
+        self.lines
+            .iter()
+            .filter_map(|line| {
+                line.iter()
+                    .map(|&index| draw_turns.get(&self.values[index]).copied())
+                    .collect::<Option<Vec<usize>>>()
+                    .and_then(|turns| turns.into_iter().max())
+                    .map(|turn| (turn, line.as_slice()))
+            })
+            .min_by_key(|&(turn, _)| turn)
+    }
+
+    /// what `score` would be if exactly the numbers drawn up to `turn` were marked
+    pub fn score_at_turn(
+        &self,
+        draw_turns: &HashMap<i32, usize>,
+        turn: usize,
+        last_draw: i32,
+    ) -> i32 {
+        let unmarked: i32 = self
+            .values
+            .iter()
+            .filter(|value| draw_turns.get(value).is_none_or(|&drawn| drawn > turn))
+            .sum();
+        unmarked * last_draw
     }
 
     fn sum_of_unmarked(&self) -> i32 {
@@ -75,6 +169,41 @@ impl Board {
     }
 }
 
+const BOLD: &str = "\x1b[1m";
+const BOLD_GREEN: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// `{}` wraps marked numbers in `*` and the winning line in `[]`,
+/// `{:#}` uses ANSI bold for marked numbers and bold green for the winning line
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let color = f.alternate();
+        let winning_line = self.winning_line().unwrap_or(&[]);
+        let cell_width = self
+            .values
+            .iter()
+            .map(|value| value.to_string().len())
+            .max()
+            .unwrap_or(0);
+        for (row_idx, row) in self.rows().iter().enumerate() {
+            for (col_idx, (value, marked)) in row.iter().enumerate() {
+                let index = row_idx * self.width + col_idx;
+                let cell = format!("{:>width$}", value, width = cell_width);
+                let in_winning_line = winning_line.contains(&index);
+                match (color, in_winning_line, marked) {
+                    (false, true, _) => write!(f, "[{}]", cell)?,
+                    (false, false, true) => write!(f, "*{}*", cell)?,
+                    (true, true, _) => write!(f, " {}{}{} ", BOLD_GREEN, cell, RESET)?,
+                    (true, false, true) => write!(f, " {}{}{} ", BOLD, cell, RESET)?,
+                    (_, false, false) => write!(f, " {} ", cell)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,6 +239,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_has_won_column() {
+        let values = vec![1, 2, 3, 4, 5, 6];
+        let mut board = Board::new(values, 3);
+        board.mark(2);
+        board.mark(2);
+        assert!(!board.has_won());
+        board.mark(5);
+        assert!(board.has_won());
+    }
+
+    #[test]
+    fn test_win_conditions() {
+        let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut board = Board::new(values, 3);
+        board.set_win_condition(&Diagonals);
+        board.mark(1);
+        board.mark(2);
+        board.mark(3);
+        assert!(!board.has_won());
+        board.mark(5);
+        board.mark(9);
+        assert!(board.has_won());
+        assert_eq!(board.winning_line(), Some([0, 4, 8].as_slice()));
+        assert_eq!(board.score(9), (4 + 6 + 7 + 8) * 9);
+
+        board.set_win_condition(&FourCorners);
+        assert!(!board.has_won());
+        board.mark(7);
+        assert!(board.has_won());
+
+        board.set_win_condition(&Blackout);
+        assert!(!board.has_won());
+    }
+
+    #[test]
+    fn test_display() {
+        let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 10];
+        let mut board = Board::new(values, 3);
+        board.mark(2);
+        assert_eq!(
+            format!("{}", board),
+            "  1 * 2*  3 \n  4   5   6 \n  7   8  10 \n"
+        );
+        board.mark(5);
+        board.mark(8);
+        assert_eq!(
+            format!("{}", board),
+            "  1 [ 2]  3 \n  4 [ 5]  6 \n  7 [ 8] 10 \n"
+        );
+        assert_eq!(
+            format!("{:#}", board).lines().next(),
+            Some("  1  \x1b[1;32m 2\x1b[0m   3 ")
+        );
+    }
+
+    #[test]
+    fn test_mark_unknown_value() {
+        let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut board = Board::new(values, 3);
+        assert!(!board.mark(10));
+        assert!(board.marks.iter().all(|m| !m));
+        assert!(!board.has_won());
+    }
+
     #[test]
     fn test_has_won() {
         let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -1,28 +1,97 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Error, Formatter};
 use std::str::FromStr;
 
 use crate::Board::*;
+use crate::WinCondition::*;
+
+/// Something that happened during play, turns count from 1
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Draw {
+        turn: usize,
+        number: i32,
+    },
+    BoardMarked {
+        turn: usize,
+        board: usize,
+        number: i32,
+    },
+    BoardWon {
+        turn: usize,
+        board: usize,
+        rank: usize,
+        score: i32,
+    },
+}
+
+/// How a board finished, boards winning on the same turn are ranked by index
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finish {
+    pub rank: usize,
+    pub turn: usize,
+    pub line: Vec<usize>,
+    pub score: i32,
+}
 
 #[derive(Debug)]
 pub struct Game {
     boards: Vec<Board>,
     draws: Vec<i32>,
     counter: usize,
+    events: Vec<Event>,
+    finishes: Vec<Option<Finish>>,
 }
 
 impl Game {
     fn new(boards: Vec<Board>, draws: Vec<i32>) -> Self {
         Self {
+            finishes: vec![None; boards.len()],
             boards,
             draws,
             counter: 0,
+            events: vec![],
+        }
+    }
+
+    /// play with house rules, every board wins on `condition` instead of rows and columns
+    pub fn with_win_condition(mut self, condition: impl WinCondition) -> Self {
+        for board in &mut self.boards {
+            board.set_win_condition(&condition);
         }
+        self
     }
 
     fn tick(&mut self) {
         let draw = self.current_draw();
         self.counter += 1;
-        for board in &mut self.boards {
-            board.mark(draw);
+        let turn = self.counter;
+        self.events.push(Event::Draw { turn, number: draw });
+        for (board_idx, board) in self.boards.iter_mut().enumerate() {
+            if board.mark(draw) {
+                self.events.push(Event::BoardMarked {
+                    turn,
+                    board: board_idx,
+                    number: draw,
+                });
+            }
+            if board.has_won() && self.finishes[board_idx].is_none() {
+                let line = board.winning_line().unwrap().to_vec();
+                let rank = self.finishes.iter().flatten().count() + 1;
+                let score = board.score(draw);
+                self.events.push(Event::BoardWon {
+                    turn,
+                    board: board_idx,
+                    rank,
+                    score,
+                });
+                self.finishes[board_idx] = Some(Finish {
+                    rank,
+                    turn,
+                    line,
+                    score,
+                });
+            }
         }
     }
 
@@ -30,73 +99,254 @@ impl Game {
         self.draws[self.counter]
     }
 
-    fn last_draw(&self) -> i32 {
-        self.draws[self.counter - 1]
+    fn last_draw(&self) -> Option<i32> {
+        self.counter.checked_sub(1).map(|i| self.draws[i])
     }
 
-    fn winning_score(&self) -> Option<i32> {
-        for board in &self.boards {
-            if board.has_won() {
-                return Some(board.score(self.last_draw()));
-            }
+    /// make the next draw, returns false once every number has been drawn
+    pub fn step(&mut self) -> bool {
+        if self.counter < self.draws.len() {
+            self.tick();
+            true
+        } else {
+            false
         }
-        None
+    }
+
+    fn finish_with_rank(&self, rank: usize) -> Option<&Finish> {
+        self.finishes
+            .iter()
+            .flatten()
+            .find(|finish| finish.rank == rank)
     }
 
     pub fn play_until_winner(&mut self) -> Option<i32> {
         while self.counter < self.draws.len() {
             self.tick();
-            if let Some(score) = self.winning_score() {
-                return Some(score);
+            if let Some(finish) = self.finish_with_rank(1) {
+                return Some(finish.score);
             }
         }
         None
     }
 
     pub fn play_until_last_winner(&mut self) -> Option<i32> {
-        let num_boards = self.boards.len();
-        let mut winning_boards: Vec<Option<i32>> = vec![None; num_boards];
         while self.counter < self.draws.len() {
             self.tick();
-            for (board_idx, board) in self.boards.iter().enumerate() {
-                if board.has_won() {
-                    let score = board.score(self.last_draw());
-                    winning_boards[board_idx] = Some(score);
-                    if winning_boards.iter().all(|b| b.is_some()) {
-                        return Some(score);
-                    }
-                }
+            if self.finishes.iter().all(|finish| finish.is_some()) {
+                return self
+                    .finish_with_rank(self.boards.len())
+                    .map(|finish| finish.score);
             }
         }
         None
     }
+
+    /// play every remaining draw, then report how each board finished
+    /// (indexed by board, `None` for boards that never won)
+    pub fn play_all(&mut self) -> &[Option<Finish>] {
+        while self.step() {}
+        &self.finishes
+    }
+
+    /// Every board's finish worked out from the draw order alone, without
+    /// ticking through the draws. Matches `play_all` on a fresh game.
+    pub fn solve(&self) -> Vec<Option<Finish>> {
+        let mut draw_turns = HashMap::new();
+        for (i, draw) in self.draws.iter().enumerate() {
+            draw_turns.entry(*draw).or_insert(i + 1);
+        }
+        let wins: Vec<Option<(usize, &[usize])>> = self
+            .boards
+            .iter()
+            .map(|board| board.winning_turn(&draw_turns))
+            .collect();
+
+        let mut order: Vec<(usize, usize)> = wins
+            .iter()
+            .enumerate()
+            .filter_map(|(board_idx, win)| win.map(|(turn, _)| (turn, board_idx)))
+            .collect();
+        order.sort_unstable();
+
+        let mut finishes = vec![None; self.boards.len()];
+        for (rank, (turn, board_idx)) in order.into_iter().enumerate() {
+            let (_, line) = wins[board_idx].unwrap();
+            let last_draw = self.draws[turn - 1];
+            finishes[board_idx] = Some(Finish {
+                rank: rank + 1,
+                turn,
+                line: line.to_vec(),
+                score: self.boards[board_idx].score_at_turn(&draw_turns, turn, last_draw),
+            });
+        }
+        finishes
+    }
+
+    /// the same as `play_until_winner`, without simulating
+    pub fn solve_winner(&self) -> Option<i32> {
+        let finishes = self.solve();
+        finishes
+            .iter()
+            .flatten()
+            .find(|finish| finish.rank == 1)
+            .map(|finish| finish.score)
+    }
+
+    /// the same as `play_until_last_winner`, without simulating
+    pub fn solve_last_winner(&self) -> Option<i32> {
+        let finishes = self.solve();
+        finishes
+            .iter()
+            .map(|finish| finish.as_ref().map(|finish| (finish.rank, finish.score)))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+            .map(|(_, score)| score)
+    }
+
+    /// everything that has happened so far, in order
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+}
+
+/// The current draw and every board, `{:#}` renders the boards with color
+impl Display for Game {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self.last_draw() {
+            Some(draw) => writeln!(
+                f,
+                "turn {} of {}, drew {}",
+                self.counter,
+                self.draws.len(),
+                draw
+            )?,
+            None => writeln!(f, "turn 0 of {}, nothing drawn yet", self.draws.len())?,
+        }
+        for (board_idx, board) in self.boards.iter().enumerate() {
+            match &self.finishes[board_idx] {
+                Some(finish) => writeln!(
+                    f,
+                    "\nboard {} won on turn {}, rank {}, score {}",
+                    board_idx + 1,
+                    finish.turn,
+                    finish.rank,
+                    finish.score
+                )?,
+                None => writeln!(f, "\nboard {}", board_idx + 1)?,
+            }
+            if f.alternate() {
+                write!(f, "{:#}", board)?;
+            } else {
+                write!(f, "{}", board)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// parse one board's rows, `number` counts from 1 and is only used in errors
+fn parse_board(number: usize, rows: &[&str]) -> Result<Board, String> {
+    let mut values = vec![];
+    let mut width = None;
+    for (row_idx, row) in rows.iter().enumerate() {
+        let cells = row
+            .split_whitespace()
+            .map(|cell| {
+                cell.parse::<i32>().map_err(|_| {
+                    format!(
+                        "board {} row {}: invalid number \"{}\"",
+                        number,
+                        row_idx + 1,
+                        cell
+                    )
+                })
+            })
+            .collect::<Result<Vec<i32>, String>>()?;
+        match width {
+            None => width = Some(cells.len()),
+            Some(width) if width != cells.len() => {
+                return Err(format!(
+                    "board {} row {} has {} numbers, expected {}",
+                    number,
+                    row_idx + 1,
+                    cells.len(),
+                    width
+                ))
+            }
+            _ => (),
+        }
+        values.extend(cells);
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(value) = values.iter().find(|value| !seen.insert(**value)) {
+        return Err(format!("board {} has {} more than once", number, value));
+    }
+    Ok(Board::new(values, width.unwrap()))
 }
 
 impl FromStr for Game {
     type Err = String;
 
+    /// Expects a comma separated line of draws, then boards separated by
+    /// blank lines. `\r\n` line endings and extra blank lines after the
+    /// draws are fine.
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut draws = vec![];
-        let mut boards = vec![];
-        for (i, input) in input.split("\n\n").enumerate() {
-            if i == 0 {
-                draws = input.split(',').map(|draw| draw.parse().unwrap()).collect();
-            } else {
-                let grid: Vec<Vec<i32>> = input
-                    .trim()
-                    .split('\n')
-                    .map(|line| {
-                        line.trim()
-                            .split_whitespace()
-                            .map(|cell| cell.parse::<i32>().unwrap())
-                            .collect()
-                    })
-                    .collect();
-                let values = grid.iter().flatten().copied().collect();
-                let width = grid[0].len();
-                boards.push(Board::new(values, width));
+        // a blank first line would otherwise read the first board as draws
+        if input
+            .lines()
+            .next()
+            .is_none_or(|line| line.trim().is_empty())
+        {
+            return Err("empty draw list".to_string());
+        }
+
+        // group non blank lines into sections, `lines` already drops any `\r`
+        let mut sections: Vec<Vec<&str>> = vec![];
+        let mut in_section = false;
+        for line in input.lines().map(str::trim) {
+            match (line.is_empty(), in_section) {
+                (true, _) => in_section = false,
+                (false, true) => sections.last_mut().unwrap().push(line),
+                (false, false) => {
+                    sections.push(vec![line]);
+                    in_section = true;
+                }
             }
         }
+
+        let (draw_section, board_sections) = sections.split_first().unwrap();
+        if draw_section.len() != 1 {
+            return Err("draws must be a single line, followed by a blank line".to_string());
+        }
+        let draws = draw_section[0]
+            .split(',')
+            .map(|draw| {
+                draw.trim()
+                    .parse::<i32>()
+                    .map_err(|_| format!("invalid draw \"{}\"", draw.trim()))
+            })
+            .collect::<Result<Vec<i32>, String>>()?;
+
+        let boards = board_sections
+            .iter()
+            .enumerate()
+            .map(|(i, rows)| parse_board(i + 1, rows))
+            .collect::<Result<Vec<Board>, String>>()?;
+        if boards.is_empty() {
+            return Err("no boards".to_string());
+        }
+
+        let on_boards: HashSet<i32> = boards
+            .iter()
+            .flat_map(|board| board.values.iter().copied())
+            .collect();
+        if let Some(draw) = draws.iter().find(|draw| !on_boards.contains(draw)) {
+            return Err(format!("draw {} does not appear on any board", draw));
+        }
+
         Ok(Self::new(boards, draws))
     }
 }
@@ -123,6 +373,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_crlf_and_trailing_blank_lines() {
+        let input = fs::read_to_string("input.test").unwrap();
+        let expected = input.parse::<Game>().unwrap();
+        let game = format!("{}\n\n\n", input.replace('\n', "\r\n"))
+            .parse::<Game>()
+            .unwrap();
+        assert_eq!(game.draws, expected.draws);
+        assert_eq!(game.boards.len(), 3);
+        assert_eq!(game.boards[2].values, expected.boards[2].values);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        [
+            ("", "empty draw list"),
+            ("\n\n", "empty draw list"),
+            ("\n\n1 2\n3 4", "empty draw list"),
+            ("  \n1,2\n\n1 2\n3 4", "empty draw list"),
+            ("1,2\n\n", "no boards"),
+            ("1,x\n\n1 2\n3 4", "invalid draw \"x\""),
+            (
+                "1,2\n\n1 2\n3 4 5",
+                "board 1 row 2 has 3 numbers, expected 2",
+            ),
+            (
+                "1,2\n\n1 2\n3 4\n\n1 2\n3 1",
+                "board 2 has 1 more than once",
+            ),
+            ("1,9\n\n1 2\n3 4", "draw 9 does not appear on any board"),
+            ("1,2\n\n1 2\n3 -", "board 1 row 2: invalid number \"-\""),
+            (
+                "1,2\n3,4\n\n1 2\n3 4",
+                "draws must be a single line, followed by a blank line",
+            ),
+        ]
+        .iter()
+        .for_each(|(input, expected)| {
+            assert_eq!(input.parse::<Game>().err().as_deref(), Some(*expected));
+        });
+    }
+
+    #[test]
+    fn test_display() {
+        let mut game = Game::new(
+            vec![
+                Board::new(vec![1, 2, 3, 4], 2),
+                Board::new(vec![5, 6, 7, 8], 2),
+            ],
+            vec![1, 5, 2],
+        );
+        assert_eq!(
+            format!("{}", game),
+            "turn 0 of 3, nothing drawn yet\n\nboard 1\n 1  2 \n 3  4 \n\nboard 2\n 5  6 \n 7  8 \n"
+        );
+        game.play_all();
+        assert_eq!(
+            format!("{}", game),
+            "turn 3 of 3, drew 2\n\
+             \nboard 1 won on turn 3, rank 1, score 14\n[1][2]\n 3  4 \n\
+             \nboard 2\n*5* 6 \n 7  8 \n"
+        );
+        assert!(format!("{:#}", game).contains("\x1b[1m5\x1b[0m"));
+    }
+
     #[test]
     fn test_tick() {
         let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -135,6 +450,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_win_condition_variants() {
+        let input = fs::read_to_string("input.test").unwrap();
+        let score = |game: Game| game.with_win_condition(Blackout).play_until_winner();
+        // nothing is left unmarked on a blacked out board
+        assert_eq!(score(input.parse().unwrap()), Some(0));
+
+        let mut game = input
+            .parse::<Game>()
+            .unwrap()
+            .with_win_condition(AnyOf(vec![Box::new(RowsAndColumns), Box::new(Diagonals)]));
+        // a diagonal fills up before any row or column does
+        assert_eq!(game.play_until_winner(), Some(494));
+    }
+
+    #[test]
+    fn test_play_all() {
+        let input = fs::read_to_string("input.test").unwrap();
+        let mut game = input.parse::<Game>().unwrap();
+        let finishes = game.play_all();
+        assert_eq!(
+            finishes[2],
+            Some(Finish {
+                rank: 1,
+                turn: 12,
+                line: vec![0, 1, 2, 3, 4],
+                score: 4512
+            })
+        );
+        assert_eq!(finishes[0].as_ref().map(|f| f.rank), Some(2));
+        assert_eq!(
+            finishes[1].as_ref().map(|f| (f.rank, f.turn)),
+            Some((3, 15))
+        );
+        assert_eq!(finishes[1].as_ref().map(|f| f.score), Some(1924));
+    }
+
+    #[test]
+    fn test_board_that_never_wins() {
+        let mut game = Game::new(
+            vec![
+                Board::new(vec![1, 2, 3, 4], 2),
+                Board::new(vec![5, 6, 7, 8], 2),
+            ],
+            vec![1, 5, 2],
+        );
+        let finishes = game.play_all();
+        assert_eq!(finishes[0].as_ref().map(|f| f.score), Some(7 * 2));
+        assert_eq!(finishes[1], None);
+        assert_eq!(game.play_until_last_winner(), None);
+    }
+
+    #[test]
+    fn test_solve_matches_simulation() {
+        for filename in ["input.test", "input"] {
+            let input = fs::read_to_string(filename).unwrap();
+            for condition in [
+                "rows-and-columns",
+                "diagonals",
+                "four-corners+x",
+                "blackout",
+            ] {
+                let new_game = || {
+                    input
+                        .parse::<Game>()
+                        .unwrap()
+                        .with_win_condition(from_name(condition).unwrap())
+                };
+                let game = new_game();
+                assert_eq!(game.solve_winner(), new_game().play_until_winner());
+                assert_eq!(
+                    game.solve_last_winner(),
+                    new_game().play_until_last_winner()
+                );
+                assert_eq!(game.solve(), new_game().play_all());
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_board_that_never_wins() {
+        let game = Game::new(
+            vec![
+                Board::new(vec![1, 2, 3, 4], 2),
+                Board::new(vec![5, 6, 7, 8], 2),
+            ],
+            vec![1, 5, 2],
+        );
+        assert_eq!(game.solve_winner(), Some(7 * 2));
+        assert_eq!(game.solve_last_winner(), None);
+        assert_eq!(game.solve()[1], None);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut game = Game::new(vec![Board::new(vec![1, 2, 3, 4], 2)], vec![9, 1, 2]);
+        game.play_all();
+        assert_eq!(
+            game.events(),
+            [
+                Event::Draw { turn: 1, number: 9 },
+                Event::Draw { turn: 2, number: 1 },
+                Event::BoardMarked {
+                    turn: 2,
+                    board: 0,
+                    number: 1
+                },
+                Event::Draw { turn: 3, number: 2 },
+                Event::BoardMarked {
+                    turn: 3,
+                    board: 0,
+                    number: 2
+                },
+                Event::BoardWon {
+                    turn: 3,
+                    board: 0,
+                    rank: 1,
+                    score: 14
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_gameplay() {
         let values = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -142,25 +580,24 @@ mod tests {
         let mut game = Game::new(vec![Board::new(values, 3)], draws);
 
         game.tick();
-        assert_eq!(game.boards[0].has_won(), false);
+        assert!(!game.boards[0].has_won());
         assert_eq!(
             game.boards[0].marks,
             vec![true, false, false, false, false, false, false, false, false]
         );
 
         game.tick();
-        assert_eq!(game.boards[0].has_won(), false);
+        assert!(!game.boards[0].has_won());
         assert_eq!(
             game.boards[0].marks,
             vec![true, true, false, false, false, false, false, false, false]
         );
 
         game.tick();
-        assert_eq!(game.boards[0].has_won(), true);
+        assert!(game.boards[0].has_won());
         assert_eq!(
             game.boards[0].marks,
             vec![true, true, true, false, false, false, false, false, false]
         );
     }
 }
-
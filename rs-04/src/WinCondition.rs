@@ -0,0 +1,165 @@
+use std::fmt::Debug;
+
+/// Decides which patterns of marks win a board.
+///
+/// A condition is a set of lines, each line a group of cell indices
+/// (`row * width + column`). A board has won once every cell of any one of
+/// its lines is marked, so boards only need a counter per line to check.
+pub trait WinCondition: Debug {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>>;
+}
+
+impl<T: WinCondition + ?Sized> WinCondition for Box<T> {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        (**self).lines(width, height)
+    }
+}
+
+/// look up a built in condition by name, names joined with `+` win on any of them
+pub fn from_name(name: &str) -> Result<Box<dyn WinCondition>, String> {
+    if name.contains('+') {
+        let conditions = name.split('+').map(from_name).collect::<Result<_, _>>()?;
+        return Ok(Box::new(AnyOf(conditions)));
+    }
+    match name {
+        "rows-and-columns" => Ok(Box::new(RowsAndColumns)),
+        "diagonals" => Ok(Box::new(Diagonals)),
+        "four-corners" => Ok(Box::new(FourCorners)),
+        "x" => Ok(Box::new(XPattern)),
+        "blackout" => Ok(Box::new(Blackout)),
+        _ => Err(format!("Unknown win condition \"{}\"", name)),
+    }
+}
+
+/// any full row or column, the standard rules
+#[derive(Debug, Clone, Copy)]
+pub struct RowsAndColumns;
+
+impl WinCondition for RowsAndColumns {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        let rows = (0..height).map(|row| (0..width).map(|col| row * width + col).collect());
+        let cols = (0..width).map(|col| (0..height).map(|row| row * width + col).collect());
+        rows.chain(cols).collect()
+    }
+}
+
+/// either full diagonal, only square boards have diagonals
+#[derive(Debug, Clone, Copy)]
+pub struct Diagonals;
+
+impl WinCondition for Diagonals {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        if width != height {
+            return vec![];
+        }
+        vec![
+            (0..width).map(|i| i * width + i).collect(),
+            (0..width).map(|i| i * width + (width - 1 - i)).collect(),
+        ]
+    }
+}
+
+/// all four corner cells
+#[derive(Debug, Clone, Copy)]
+pub struct FourCorners;
+
+impl WinCondition for FourCorners {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        let mut corners = vec![0, width - 1, (height - 1) * width, height * width - 1];
+        corners.sort_unstable();
+        corners.dedup();
+        vec![corners]
+    }
+}
+
+/// both diagonals at once, an X across a square board
+#[derive(Debug, Clone, Copy)]
+pub struct XPattern;
+
+impl WinCondition for XPattern {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        let mut cells: Vec<usize> = Diagonals.lines(width, height).concat();
+        if cells.is_empty() {
+            return vec![];
+        }
+        cells.sort_unstable();
+        cells.dedup();
+        vec![cells]
+    }
+}
+
+/// every cell on the board
+#[derive(Debug, Clone, Copy)]
+pub struct Blackout;
+
+impl WinCondition for Blackout {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        vec![(0..width * height).collect()]
+    }
+}
+
+/// wins if any of the inner conditions would
+#[derive(Debug)]
+pub struct AnyOf(pub Vec<Box<dyn WinCondition>>);
+
+impl WinCondition for AnyOf {
+    fn lines(&self, width: usize, height: usize) -> Vec<Vec<usize>> {
+        self.0
+            .iter()
+            .flat_map(|condition| condition.lines(width, height))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_and_columns() {
+        let lines = RowsAndColumns.lines(3, 2);
+        assert_eq!(
+            lines,
+            vec![
+                vec![0, 1, 2],
+                vec![3, 4, 5],
+                vec![0, 3],
+                vec![1, 4],
+                vec![2, 5]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagonals() {
+        assert_eq!(Diagonals.lines(3, 3), vec![vec![0, 4, 8], vec![2, 4, 6]]);
+        assert!(Diagonals.lines(3, 2).is_empty());
+        assert_eq!(XPattern.lines(3, 3), vec![vec![0, 2, 4, 6, 8]]);
+    }
+
+    #[test]
+    fn test_corners_and_blackout() {
+        assert_eq!(FourCorners.lines(5, 5), vec![vec![0, 4, 20, 24]]);
+        assert_eq!(FourCorners.lines(1, 1), vec![vec![0]]);
+        assert_eq!(Blackout.lines(2, 2), vec![vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(from_name("x").unwrap().lines(3, 3), XPattern.lines(3, 3));
+        assert_eq!(
+            from_name("rows-and-columns+diagonals")
+                .unwrap()
+                .lines(3, 3)
+                .len(),
+            8
+        );
+        assert!(from_name("rows+columns").is_err());
+    }
+
+    #[test]
+    fn test_any_of() {
+        let condition = AnyOf(vec![Box::new(Diagonals), Box::new(FourCorners)]);
+        assert_eq!(condition.lines(3, 3).len(), 3);
+    }
+}
@@ -1,18 +1,75 @@
+use std::env;
 use std::fs;
+use std::io::{self, IsTerminal};
 
-mod Game;
+#[allow(non_snake_case)]
 mod Board;
+#[allow(non_snake_case)]
+mod Game;
+#[allow(non_snake_case)]
+mod WinCondition;
 
 fn main() {
-    let input = fs::read_to_string("input").unwrap();
-    let mut game = input.parse::<Game::Game>().unwrap();
+    // house rules can be picked by name, e.g. `cargo run -- rows-and-columns+diagonals`,
+    // and `--step` plays one draw at a time
+    let args: Vec<String> = env::args().skip(1).collect();
+    let step = args.iter().any(|arg| arg == "--step");
+    let win_condition = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or("rows-and-columns", String::as_str);
+    let new_game = || {
+        fs::read_to_string("input")
+            .unwrap()
+            .parse::<Game::Game>()
+            .unwrap()
+            .with_win_condition(WinCondition::from_name(win_condition).unwrap())
+    };
+
+    if step {
+        play_step_by_step(new_game());
+        return;
+    }
+
+    let mut game = new_game();
+    println!("part 1 analytic: {}", game.solve_winner().unwrap());
     let winning_score = game.play_until_winner().unwrap();
     println!("part 1: {}", winning_score);
 
-    let input = fs::read_to_string("input").unwrap();
-    let mut game = input.parse::<Game::Game>().unwrap();
+    let mut game = new_game();
+    println!("part 2 analytic: {}", game.solve_last_winner().unwrap());
     let last_winning_score = game.play_until_last_winner().unwrap();
     println!("part 2: {}", last_winning_score);
+
+    let mut game = new_game();
+    let finishes = game.play_all();
+    let never_won = finishes.iter().filter(|finish| finish.is_none()).count();
+    let last_turn = finishes.iter().flatten().map(|finish| finish.turn).max();
+    let wins = game
+        .events()
+        .iter()
+        .filter(|event| matches!(event, Game::Event::BoardWon { .. }))
+        .count();
+    println!(
+        "{} boards won, the last on turn {:?}, {} never won",
+        wins, last_turn, never_won
+    );
+}
+
+/// print the game after every draw, waiting for enter in between (q to quit)
+fn play_step_by_step(mut game: Game::Game) {
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    while game.step() {
+        if color {
+            println!("{:#}", game);
+        } else {
+            println!("{}", game);
+        }
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).unwrap() == 0 || line.trim() == "q" {
+            break;
+        }
+    }
 }
 
 #[cfg(test)]
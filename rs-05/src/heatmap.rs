@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::Position;
+
+/// an inclusive box of points, the part of the matrix that gets drawn
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Bounds {
+    /// the smallest box holding every point in the matrix, `None` if it's empty
+    pub fn of(matrix: &HashMap<Position, i32>) -> Option<Self> {
+        let xs = matrix.keys().map(|point| point.0);
+        let ys = matrix.keys().map(|point| point.1);
+        Some(Self {
+            min: (xs.clone().min()?, ys.clone().min()?),
+            max: (xs.max()?, ys.max()?),
+        })
+    }
+
+    fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    /// every point row by row, top to bottom then left to right
+    fn points(&self) -> impl Iterator<Item = Position> + '_ {
+        (self.min.1..=self.max.1).flat_map(move |y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+/// parses `min_x,min_y,max_x,max_y`
+impl FromStr for Bounds {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let values = input
+            .split(',')
+            .map(|value| value.trim().parse::<i32>())
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|_| format!("Invalid bounds \"{}\"", input))?;
+        match values[..] {
+            [min_x, min_y, max_x, max_y] if min_x <= max_x && min_y <= max_y => Ok(Self {
+                min: (min_x, min_y),
+                max: (max_x, max_y),
+            }),
+            _ => Err(format!(
+                "Expected min_x,min_y,max_x,max_y, got \"{}\"",
+                input
+            )),
+        }
+    }
+}
+
+fn count_at(matrix: &HashMap<Position, i32>, point: &Position) -> i32 {
+    matrix.get(point).copied().unwrap_or(0)
+}
+
+/// Same layout as the puzzle's examples: `.` where no line is, otherwise the
+/// number of lines (`+` once there are more than 9)
+pub fn to_ascii(matrix: &HashMap<Position, i32>, bounds: Bounds) -> String {
+    let mut result = String::with_capacity((bounds.width() + 1) * bounds.height());
+    for (i, point) in bounds.points().enumerate() {
+        result.push(match count_at(matrix, &point) {
+            0 => '.',
+            count @ 1..=9 => char::from_digit(count as u32, 10).unwrap(),
+            _ => '+',
+        });
+        if (i + 1) % bounds.width() == 0 {
+            result.push('\n');
+        }
+    }
+    result
+}
+
+/// a plain (P2) greyscale image, brighter where more lines overlap
+pub fn to_pgm(matrix: &HashMap<Position, i32>, bounds: Bounds) -> String {
+    let max_count = bounds
+        .points()
+        .map(|point| count_at(matrix, &point))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut result = format!(
+        "P2\n{} {}\n{}\n",
+        bounds.width(),
+        bounds.height(),
+        max_count
+    );
+    for row in bounds.points().collect::<Vec<_>>().chunks(bounds.width()) {
+        let values: Vec<String> = row
+            .iter()
+            .map(|point| count_at(matrix, point).to_string())
+            .collect();
+        result.push_str(&values.join(" "));
+        result.push('\n');
+    }
+    result
+}
+
+/// black where no line is, then blue for a single line shading to red at the most overlaps
+fn color(count: i32, max_count: i32) -> (u8, u8, u8) {
+    if count == 0 {
+        return (0, 0, 0);
+    }
+    let heat = if max_count > 1 {
+        (count - 1) as f64 / (max_count - 1) as f64
+    } else {
+        0.0
+    };
+    ((255.0 * heat) as u8, 0, (255.0 * (1.0 - heat)) as u8)
+}
+
+/// a plain (P3) color image, see `color` for the scale
+pub fn to_ppm(matrix: &HashMap<Position, i32>, bounds: Bounds) -> String {
+    let max_count = bounds
+        .points()
+        .map(|point| count_at(matrix, &point))
+        .max()
+        .unwrap_or(0);
+    let mut result = format!("P3\n{} {}\n255\n", bounds.width(), bounds.height());
+    for row in bounds.points().collect::<Vec<_>>().chunks(bounds.width()) {
+        let values: Vec<String> = row
+            .iter()
+            .map(|point| {
+                let (r, g, b) = color(count_at(matrix, point), max_count);
+                format!("{} {} {}", r, g, b)
+            })
+            .collect();
+        result.push_str(&values.join("  "));
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_sparse_matrix, get_input, LineMode};
+
+    #[test]
+    fn test_ascii_sample() {
+        let matrix = build_sparse_matrix(&get_input("input.test"), LineMode::Diagonal);
+        let bounds = Bounds::of(&matrix).unwrap();
+        assert_eq!(
+            bounds,
+            Bounds {
+                min: (0, 0),
+                max: (9, 9)
+            }
+        );
+        assert_eq!(
+            to_ascii(&matrix, bounds),
+            "1.1....11.\n\
+             .111...2..\n\
+             ..2.1.111.\n\
+             ...1.2.2..\n\
+             .112313211\n\
+             ...1.2....\n\
+             ..1...1...\n\
+             .1.....1..\n\
+             1.......1.\n\
+             222111....\n"
+        );
+    }
+
+    #[test]
+    fn test_crop() {
+        let matrix = build_sparse_matrix(&get_input("input.test"), LineMode::Axis);
+        let bounds = "6,3,9,4".parse().unwrap();
+        assert_eq!(to_ascii(&matrix, bounds), ".1..\n1211\n");
+        assert_eq!(to_pgm(&matrix, bounds), "P2\n4 2\n2\n0 1 0 0\n1 2 1 1\n");
+        assert!("1,2,3".parse::<Bounds>().is_err());
+        assert!("3,0,1,0".parse::<Bounds>().is_err());
+    }
+
+    #[test]
+    fn test_ppm() {
+        let matrix = HashMap::from([((0, 0), 1), ((1, 0), 3)]);
+        let bounds = "0,0,2,0".parse().unwrap();
+        assert_eq!(
+            to_ppm(&matrix, bounds),
+            "P3\n3 1\n255\n0 0 255  255 0 0  0 0 0\n"
+        );
+    }
+}
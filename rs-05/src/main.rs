@@ -1,22 +1,89 @@
 use std::collections::HashMap;
-use std::{cmp, fs};
+use std::{cmp, env, fs};
+
+use heatmap::Bounds;
+use vent_field::VentField;
+
+mod heatmap;
+mod sweep;
+mod vent_field;
 
 fn main() {
-    println!("part 1: {}", count_overlapping_lines("input", false));
-    println!("part 2: {}", count_overlapping_lines("input", true));
+    // `cargo run -- <ascii|pgm|ppm> [min_x,min_y,max_x,max_y]` prints the part 2 overlaps instead
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(format) = args.first() {
+        let matrix = build_sparse_matrix(&get_input("input"), LineMode::Diagonal);
+        let bounds = match args.get(1) {
+            Some(bounds) => bounds.parse().unwrap(),
+            None => Bounds::of(&matrix).unwrap(),
+        };
+        match format.as_str() {
+            "ascii" => print!("{}", heatmap::to_ascii(&matrix, bounds)),
+            "pgm" => print!("{}", heatmap::to_pgm(&matrix, bounds)),
+            "ppm" => print!("{}", heatmap::to_ppm(&matrix, bounds)),
+            _ => println!("unknown format \"{}\"", format),
+        }
+        return;
+    }
+
+    println!(
+        "part 1: {}",
+        count_overlapping_lines("input", LineMode::Axis)
+    );
+    println!(
+        "part 2: {}",
+        count_overlapping_lines("input", LineMode::Diagonal)
+    );
+    println!(
+        "any slope: {}",
+        count_overlapping_lines("input", LineMode::Any)
+    );
+    println!(
+        "part 2 sweep: {}",
+        sweep::count_overlaps(&get_input("input"), LineMode::Diagonal).unwrap()
+    );
+
+    let lines = get_input("input");
+    let field = VentField::new(&lines, LineMode::Diagonal);
+    println!("overlap histogram: {:?}", field.histogram());
+    println!("points with 3+ lines: {}", field.count_at_least(3));
+    if let Some((count, points)) = field.most_overlapped() {
+        println!("{} lines meet at {} points", count, points.len());
+        println!("lines through {:?}:", points[0]);
+        for line in field.lines_at(points[0]) {
+            println!("  {:?} -> {:?}", line.start, line.end);
+        }
+    }
 }
 
-/// lay out the lines in a sparse matrix and then count the number of coordinates where two or more
-/// lines overlap
-fn count_overlapping_lines(filename: &str, consider_diagonals: bool) -> i32 {
+/// which classes of line are laid out, lines of other classes are skipped
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LineMode {
+    /// horizontal and vertical lines only
+    Axis,
+    /// horizontal, vertical and exact 45 degree lines
+    Diagonal,
+    /// lines at any slope
+    Any,
+}
+
+/// lay out the lines in a sparse matrix of how many lines cover each point
+fn build_sparse_matrix(lines: &[Line], mode: LineMode) -> HashMap<Position, i32> {
     let mut points_sparse_matrix: HashMap<Position, i32> = HashMap::new();
-    for line in get_input(filename) {
-        for point in line.get_points(consider_diagonals) {
+    for line in lines {
+        for point in line.get_points(mode) {
             let prev_count = points_sparse_matrix.get(&point).unwrap_or(&0);
             let new_count = prev_count + 1;
             points_sparse_matrix.insert(point, new_count);
         }
     }
+    points_sparse_matrix
+}
+
+/// lay out the lines in a sparse matrix and then count the number of coordinates where two or more
+/// lines overlap
+fn count_overlapping_lines(filename: &str, mode: LineMode) -> i32 {
+    let points_sparse_matrix = build_sparse_matrix(&get_input(filename), mode);
     let mut two_plus_count = 0;
     for (_k, v) in points_sparse_matrix.iter() {
         if v >= &2 {
@@ -39,53 +106,55 @@ impl Line {
         Self { start, end }
     }
 
-    fn get_points(&self, consider_diagonals: bool) -> Vec<Position> {
-        let has_x_change = self.start.0 != self.end.0;
-        let has_y_change = self.start.1 != self.end.1;
+    /// a single point counts as both horizontal and vertical
+    fn is_axis(&self) -> bool {
+        self.start.0 == self.end.0 || self.start.1 == self.end.1
+    }
 
-        match (has_x_change, has_y_change) {
-            (true, false) => {
-                // line is horizonal
-                let start_x = cmp::min(self.start.0, self.end.0);
-                let end_x = cmp::max(self.start.0, self.end.0);
-                let y = self.start.1;
-                (start_x..=end_x).map(|x| (x, y)).collect()
-            }
-            (false, true) => {
-                // line is vertical
-                let start_y = cmp::min(self.start.1, self.end.1);
-                let end_y = cmp::max(self.start.1, self.end.1);
-                let x = self.start.0;
-                (start_y..=end_y).map(|y| (x, y)).collect()
-            }
-            (true, true) => {
-                // line is diagonal
-                if consider_diagonals {
-                    self.get_diagonal_points()
-                } else {
-                    vec![]
-                }
-            }
-            _ => panic!("unexpected line with no direction"),
+    fn is_diagonal(&self) -> bool {
+        (self.end.0 - self.start.0).abs() == (self.end.1 - self.start.1).abs()
+    }
+
+    fn get_points(&self, mode: LineMode) -> Vec<Position> {
+        let included = match mode {
+            LineMode::Axis => self.is_axis(),
+            LineMode::Diagonal => self.is_axis() || self.is_diagonal(),
+            LineMode::Any => true,
+        };
+        if included {
+            self.rasterize()
+        } else {
+            vec![]
         }
     }
 
-    fn get_diagonal_points(&self) -> Vec<Position> {
-        let mut as_array = [self.start, self.end];
-        as_array.sort_unstable();
-        let [start_pos, end_pos] = as_array;
+    /// Bresenham's line algorithm, works for any slope and gives the exact
+    /// points for horizontal, vertical and 45 degree lines
+    fn rasterize(&self) -> Vec<Position> {
+        let (mut x, mut y) = self.start;
+        let (end_x, end_y) = self.end;
+        let dx = (end_x - x).abs();
+        let dy = -(end_y - y).abs();
+        let step_x = (end_x - x).signum();
+        let step_y = (end_y - y).signum();
+        let mut error = dx + dy;
 
-        (start_pos.0..=end_pos.0)
-            .enumerate()
-            .map(|(i, x)| {
-                let y = if start_pos.1 < end_pos.1 {
-                    start_pos.1 + i as i32
-                } else {
-                    start_pos.1 - i as i32
-                };
-                (x, y)
-            })
-            .collect()
+        let mut points = Vec::with_capacity(cmp::max(dx, -dy) as usize + 1);
+        loop {
+            points.push((x, y));
+            if (x, y) == self.end {
+                return points;
+            }
+            let doubled_error = 2 * error;
+            if doubled_error >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled_error <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
     }
 }
 
@@ -128,23 +197,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_points() {
+        let mut points = Line::new((3, 4), (1, 4)).get_points(LineMode::Axis);
+        points.sort_unstable();
+        assert_eq!(points, vec![(1, 4), (2, 4), (3, 4)]);
+
+        let diagonal = Line::new((5, 5), (8, 2));
+        assert_eq!(diagonal.get_points(LineMode::Axis), vec![]);
+        assert_eq!(
+            diagonal.get_points(LineMode::Diagonal),
+            vec![(5, 5), (6, 4), (7, 3), (8, 2)]
+        );
+    }
+
+    #[test]
+    fn test_get_points_any_slope() {
+        let line = Line::new((0, 0), (4, 2));
+        assert_eq!(line.get_points(LineMode::Diagonal), vec![]);
+        assert_eq!(
+            line.get_points(LineMode::Any),
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+        assert_eq!(
+            Line::new((1, 5), (0, 0)).get_points(LineMode::Any),
+            vec![(1, 5), (1, 4), (1, 3), (0, 2), (0, 1), (0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_get_points_single_point() {
+        let line = Line::new((3, 3), (3, 3));
+        for mode in [LineMode::Axis, LineMode::Diagonal, LineMode::Any] {
+            assert_eq!(line.get_points(mode), vec![(3, 3)]);
+        }
+    }
+
     #[test]
     fn test_part_1_sample() {
-        assert_eq!(count_overlapping_lines("input.test", false), 5);
+        assert_eq!(count_overlapping_lines("input.test", LineMode::Axis), 5);
     }
 
     #[test]
     fn test_part_1_real() {
-        assert_eq!(count_overlapping_lines("input", false), 7468);
+        assert_eq!(count_overlapping_lines("input", LineMode::Axis), 7468);
     }
 
     #[test]
     fn test_part_2_sample() {
-        assert_eq!(count_overlapping_lines("input.test", true), 12);
+        assert_eq!(
+            count_overlapping_lines("input.test", LineMode::Diagonal),
+            12
+        );
     }
 
     #[test]
     fn test_part_2_real() {
-        assert_eq!(count_overlapping_lines("input", true), 22364);
+        assert_eq!(count_overlapping_lines("input", LineMode::Diagonal), 22364);
     }
 }
@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Line, LineMode};
+
+/// The four directions a line can run in while still hitting a lattice point
+/// at every step. A line in direction `(a, b)` is every point on
+/// `a * x + b * y = carrier` between two values of its parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    /// x and y increase together
+    Rising,
+    /// x increases while y decreases
+    Falling,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Horizontal,
+        Direction::Vertical,
+        Direction::Rising,
+        Direction::Falling,
+    ];
+
+    fn coefficients(&self) -> (i64, i64) {
+        match self {
+            Direction::Horizontal => (0, 1),
+            Direction::Vertical => (1, 0),
+            Direction::Rising => (1, -1),
+            Direction::Falling => (1, 1),
+        }
+    }
+
+    fn carrier(&self, (x, y): (i64, i64)) -> i64 {
+        let (a, b) = self.coefficients();
+        a * x + b * y
+    }
+
+    /// position along the line, y for vertical lines and x for everything else
+    fn parameter(&self, (x, y): (i64, i64)) -> i64 {
+        match self {
+            Direction::Vertical => y,
+            _ => x,
+        }
+    }
+}
+
+/// the inclusive parameter ranges covered by at least one, and at least two, lines
+#[derive(Debug, Default)]
+struct Coverage {
+    once: Vec<(i64, i64)>,
+    twice: Vec<(i64, i64)>,
+}
+
+impl Coverage {
+    /// sweep over the start and end of every range on one carrier line
+    fn sweep(ranges: &[(i64, i64)]) -> Self {
+        let mut events: Vec<(i64, i32)> = ranges
+            .iter()
+            .flat_map(|&(lo, hi)| [(lo, 1), (hi + 1, -1)])
+            .collect();
+        events.sort_unstable();
+
+        let mut coverage = Coverage::default();
+        let mut depth = 0;
+        let (mut once_start, mut twice_start) = (0, 0);
+        for (at, change) in events {
+            let before = depth;
+            depth += change;
+            match (before, depth) {
+                (0, 1) => once_start = at,
+                (1, 0) => coverage.once.push((once_start, at - 1)),
+                (1, 2) => twice_start = at,
+                (2, 1) => coverage.twice.push((twice_start, at - 1)),
+                _ => (),
+            }
+        }
+        // touching ranges come out as neighbours, join them back up
+        coverage.once = join(coverage.once);
+        coverage.twice = join(coverage.twice);
+        coverage
+    }
+
+    fn twice_contains(&self, parameter: i64) -> bool {
+        let index = self.twice.partition_point(|&(_, hi)| hi < parameter);
+        self.twice
+            .get(index)
+            .is_some_and(|&(lo, _)| lo <= parameter)
+    }
+}
+
+fn join(ranges: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let mut joined: Vec<(i64, i64)> = vec![];
+    for (lo, hi) in ranges {
+        match joined.last_mut() {
+            Some(last) if last.1 + 1 >= lo => last.1 = hi,
+            _ => joined.push((lo, hi)),
+        }
+    }
+    joined
+}
+
+fn direction_of(line: &Line) -> Option<Direction> {
+    let dx = line.end.0 - line.start.0;
+    let dy = line.end.1 - line.start.1;
+    match (dx, dy) {
+        (_, 0) => Some(Direction::Horizontal),
+        (0, _) => Some(Direction::Vertical),
+        _ if dx == dy => Some(Direction::Rising),
+        _ if dx == -dy => Some(Direction::Falling),
+        _ => None,
+    }
+}
+
+/// where two lines in different directions cross, if that's a lattice point
+fn crossing(
+    (first, first_carrier): (Direction, i64),
+    (second, second_carrier): (Direction, i64),
+) -> Option<(i64, i64)> {
+    let (a1, b1) = first.coefficients();
+    let (a2, b2) = second.coefficients();
+    let det = a1 * b2 - a2 * b1;
+    let x = first_carrier * b2 - second_carrier * b1;
+    let y = a1 * second_carrier - a2 * first_carrier;
+    if x % det != 0 || y % det != 0 {
+        return None;
+    }
+    Some((x / det, y / det))
+}
+
+/// Count the points where two or more lines overlap without visiting every
+/// point. Collinear lines are swept per carrier line to find the ranges
+/// covered twice, then every crossing between lines of different directions
+/// is checked, so the cost depends on the number of lines and not on how
+/// long they are.
+///
+/// Gives the same count as laying the lines out point by point, but only for
+/// horizontal, vertical and 45 degree lines, `LineMode::Any` is an error if
+/// any line has another slope.
+pub fn count_overlaps(lines: &[Line], mode: LineMode) -> Result<usize, String> {
+    let mut ranges: HashMap<(Direction, i64), Vec<(i64, i64)>> = HashMap::new();
+    for line in lines {
+        // a single point comes out as horizontal, so it's only counted once
+        let direction = match direction_of(line) {
+            Some(direction @ (Direction::Horizontal | Direction::Vertical)) => direction,
+            Some(_) if mode == LineMode::Axis => continue,
+            Some(direction) => direction,
+            None if mode == LineMode::Any => {
+                return Err(format!("{:?} isn't horizontal, vertical or diagonal", line))
+            }
+            None => continue,
+        };
+        let start = (line.start.0 as i64, line.start.1 as i64);
+        let end = (line.end.0 as i64, line.end.1 as i64);
+        let (lo, hi) = (direction.parameter(start), direction.parameter(end));
+        ranges
+            .entry((direction, direction.carrier(start)))
+            .or_default()
+            .push((lo.min(hi), lo.max(hi)));
+    }
+
+    let coverage: HashMap<(Direction, i64), Coverage> = ranges
+        .iter()
+        .map(|(key, ranges)| (*key, Coverage::sweep(ranges)))
+        .collect();
+
+    let twice_total: i64 = coverage
+        .values()
+        .flat_map(|coverage| coverage.twice.iter())
+        .map(|(lo, hi)| hi - lo + 1)
+        .sum();
+
+    // every point where lines of two directions cross
+    let mut crossings = HashSet::new();
+    let keys: Vec<&(Direction, i64)> = coverage.keys().collect();
+    for (i, first) in keys.iter().enumerate() {
+        for second in keys[i + 1..].iter().filter(|second| second.0 != first.0) {
+            let point = match crossing(**first, **second) {
+                Some(point) => point,
+                None => continue,
+            };
+            let on_line = |key: &(Direction, i64)| {
+                let parameter = key.0.parameter(point);
+                coverage[key]
+                    .once
+                    .iter()
+                    .any(|&(lo, hi)| lo <= parameter && parameter <= hi)
+            };
+            if on_line(first) && on_line(second) {
+                crossings.insert(point);
+            }
+        }
+    }
+
+    // a crossing already inside k twice covered ranges has been counted k
+    // times, it should be counted once whatever k is
+    let adjustment: i64 = crossings
+        .iter()
+        .map(|&point| {
+            let counted = Direction::ALL
+                .iter()
+                .filter(|direction| {
+                    coverage
+                        .get(&(**direction, direction.carrier(point)))
+                        .is_some_and(|coverage| coverage.twice_contains(direction.parameter(point)))
+                })
+                .count() as i64;
+            1 - counted
+        })
+        .sum();
+
+    Ok((twice_total + adjustment) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_input;
+
+    #[test]
+    fn test_matches_raster() {
+        for filename in ["input.test", "input"] {
+            let lines = get_input(filename);
+            for mode in [LineMode::Axis, LineMode::Diagonal] {
+                assert_eq!(
+                    count_overlaps(&lines, mode),
+                    Ok(crate::count_overlapping_lines(filename, mode) as usize)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sweep() {
+        let coverage = Coverage::sweep(&[(0, 5), (3, 8), (6, 7), (10, 10)]);
+        assert_eq!(coverage.once, vec![(0, 8), (10, 10)]);
+        assert_eq!(coverage.twice, vec![(3, 7)]);
+        assert!(coverage.twice_contains(7));
+        assert!(!coverage.twice_contains(8));
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let lines = vec![
+            // two overlapping horizontals share 1,000,001 points
+            Line::new((0, 0), (2_000_000, 0)),
+            Line::new((1_000_000, 0), (3_000_000, 0)),
+            // crosses inside the overlap, which is already counted
+            Line::new((1_500_000, -5), (1_500_000, 5)),
+            // crosses the second horizontal outside the overlap
+            Line::new((2_500_000, -1), (2_499_990, 9)),
+            // a single point on top of the first horizontal
+            Line::new((7, 0), (7, 0)),
+            // never touches anything
+            Line::new((0, 100), (5, 200)),
+        ];
+        assert_eq!(
+            count_overlaps(&lines, LineMode::Diagonal),
+            Ok(1_000_001 + 1 + 1)
+        );
+        assert_eq!(count_overlaps(&lines, LineMode::Axis), Ok(1_000_001 + 1));
+        assert!(count_overlaps(&lines, LineMode::Any).is_err());
+    }
+
+    #[test]
+    fn test_diagonal_crossings_off_the_lattice() {
+        // these cross at (0.5, 0.5), which no point lands on
+        let lines = vec![Line::new((0, 0), (1, 1)), Line::new((0, 1), (1, 0))];
+        assert_eq!(count_overlaps(&lines, LineMode::Diagonal), Ok(0));
+    }
+}
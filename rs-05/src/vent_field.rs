@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Line, LineMode, Position};
+
+/// Every point covered by the vent lines, along with which lines cover it
+pub struct VentField<'a> {
+    lines: &'a [Line],
+    covering: HashMap<Position, Vec<usize>>,
+}
+
+impl<'a> VentField<'a> {
+    pub fn new(lines: &'a [Line], mode: LineMode) -> Self {
+        let mut covering: HashMap<Position, Vec<usize>> = HashMap::new();
+        for (line_idx, line) in lines.iter().enumerate() {
+            for point in line.get_points(mode) {
+                covering.entry(point).or_default().push(line_idx);
+            }
+        }
+        Self { lines, covering }
+    }
+
+    /// the lines passing through `point`, in input order
+    pub fn lines_at(&self, point: Position) -> Vec<&'a Line> {
+        self.covering.get(&point).map_or(vec![], |indexes| {
+            indexes.iter().map(|&i| &self.lines[i]).collect()
+        })
+    }
+
+    /// how many points are covered by exactly n lines, for every n that occurs
+    pub fn histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for indexes in self.covering.values() {
+            *histogram.entry(indexes.len()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// how many points are covered by at least `k` lines
+    pub fn count_at_least(&self, k: usize) -> usize {
+        self.covering
+            .values()
+            .filter(|indexes| indexes.len() >= k)
+            .count()
+    }
+
+    /// the highest number of overlapping lines and every point (sorted) that has it
+    pub fn most_overlapped(&self) -> Option<(usize, Vec<Position>)> {
+        let max = self.covering.values().map(|indexes| indexes.len()).max()?;
+        let mut points: Vec<Position> = self
+            .covering
+            .iter()
+            .filter(|(_, indexes)| indexes.len() == max)
+            .map(|(point, _)| *point)
+            .collect();
+        points.sort_unstable();
+        Some((max, points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_overlapping_lines, get_input};
+
+    #[test]
+    fn test_matches_count_overlapping_lines() {
+        let lines = get_input("input");
+        for mode in [LineMode::Axis, LineMode::Diagonal] {
+            let field = VentField::new(&lines, mode);
+            assert_eq!(
+                field.count_at_least(2) as i32,
+                count_overlapping_lines("input", mode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_queries_sample() {
+        let lines = get_input("input.test");
+        let field = VentField::new(&lines, LineMode::Diagonal);
+
+        assert_eq!(
+            field.lines_at((4, 4)),
+            vec![
+                &Line::new((8, 0), (0, 8)),
+                &Line::new((9, 4), (3, 4)),
+                &Line::new((0, 0), (8, 8)),
+            ]
+        );
+        assert_eq!(field.lines_at((9, 9)), Vec::<&Line>::new());
+
+        assert_eq!(
+            field.histogram(),
+            BTreeMap::from([(1, 27), (2, 10), (3, 2)])
+        );
+        assert_eq!(field.count_at_least(2), 12);
+        assert_eq!(field.count_at_least(3), 2);
+        assert_eq!(field.count_at_least(4), 0);
+        assert_eq!(field.most_overlapped(), Some((3, vec![(4, 4), (6, 4)])));
+    }
+
+    #[test]
+    fn test_empty_field() {
+        let field = VentField::new(&[], LineMode::Any);
+        assert!(field.histogram().is_empty());
+        assert_eq!(field.most_overlapped(), None);
+    }
+}
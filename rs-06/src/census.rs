@@ -0,0 +1,50 @@
+/// The school as counted on one day
+#[derive(Clone, Debug, PartialEq)]
+pub struct Census {
+    pub day: usize,
+    pub total: i64,
+    /// the same buckets as the school, timers or ages depending on the model
+    pub fish_by_age: Vec<i64>,
+}
+
+/// one row per day, `day,total` then a column per bucket
+pub fn to_csv(series: &[Census]) -> String {
+    let buckets = series.first().map_or(0, |census| census.fish_by_age.len());
+    let mut header = vec!["day".to_string(), "total".to_string()];
+    header.extend((0..buckets).map(|bucket| format!("age_{}", bucket)));
+    let mut result = header.join(",");
+    result.push('\n');
+    for census in series {
+        let mut row = vec![census.day.to_string(), census.total.to_string()];
+        row.extend(census.fish_by_age.iter().map(|count| count.to_string()));
+        result.push_str(&row.join(","));
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let series = vec![
+            Census {
+                day: 0,
+                total: 2,
+                fish_by_age: vec![1, 1, 0],
+            },
+            Census {
+                day: 1,
+                total: 3,
+                fish_by_age: vec![1, 0, 2],
+            },
+        ];
+        assert_eq!(
+            to_csv(&series),
+            "day,total,age_0,age_1,age_2\n0,2,1,1,0\n1,3,1,0,2\n"
+        );
+        assert_eq!(to_csv(&[]), "day,total\n");
+    }
+}
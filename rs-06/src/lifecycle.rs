@@ -0,0 +1,229 @@
+use std::str::FromStr;
+
+/// The rules a school of fish lives by.
+///
+/// An adult has a baby every `cycle` days, and a newborn waits an extra
+/// `newborn_delay` days before its first. Each birth brings `litter` fish.
+/// With a `mortality_age`, fish die on the day they would reach that age.
+///
+/// Without a mortality age fish are only told apart by their timer, days
+/// until their next birth, so there's one bucket per timer value. With one,
+/// fish are told apart by their age in days instead, one bucket per age.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LifecycleModel {
+    cycle: usize,
+    newborn_delay: usize,
+    litter: u64,
+    mortality_age: Option<usize>,
+}
+
+impl Default for LifecycleModel {
+    /// the puzzle's lanternfish
+    fn default() -> Self {
+        Self {
+            cycle: 7,
+            newborn_delay: 2,
+            litter: 1,
+            mortality_age: None,
+        }
+    }
+}
+
+impl LifecycleModel {
+    pub fn new(
+        cycle: usize,
+        newborn_delay: usize,
+        litter: u64,
+        mortality_age: Option<usize>,
+    ) -> Result<Self, String> {
+        if cycle == 0 {
+            return Err("cycle should be at least 1 day".to_string());
+        }
+        let model = Self {
+            cycle,
+            newborn_delay,
+            litter,
+            mortality_age,
+        };
+        match mortality_age {
+            Some(age) if age <= model.newborn_timer() => Err(format!(
+                "mortality age {} should be over the newborn timer {}",
+                age,
+                model.newborn_timer()
+            )),
+            _ => Ok(model),
+        }
+    }
+
+    /// the timer a newborn starts with, 8 for the puzzle
+    pub fn newborn_timer(&self) -> usize {
+        self.cycle + self.newborn_delay - 1
+    }
+
+    /// whether fish die once they reach a mortality age
+    pub fn has_mortality(&self) -> bool {
+        self.mortality_age.is_some()
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        match self.mortality_age {
+            None => self.newborn_timer() + 1,
+            Some(age) => age,
+        }
+    }
+
+    /// The bucket for a fish from the input with the given timer. With a
+    /// mortality age that fish is assumed to be on its first cycle, the
+    /// youngest it could be.
+    pub fn bucket_for_timer(&self, timer: usize) -> Result<usize, String> {
+        if timer > self.newborn_timer() {
+            return Err(format!(
+                "Timer {} is over the newborn timer {}",
+                timer,
+                self.newborn_timer()
+            ));
+        }
+        Ok(match self.mortality_age {
+            None => timer,
+            Some(_) => self.newborn_timer() - timer,
+        })
+    }
+
+    /// whether a fish in `bucket` gives birth on the next day
+    fn gives_birth(&self, bucket: usize) -> bool {
+        match self.mortality_age {
+            None => bucket == 0,
+            Some(_) => {
+                let first = self.newborn_timer() + 1;
+                bucket + 1 >= first && (bucket + 1 - first).is_multiple_of(self.cycle)
+            }
+        }
+    }
+
+    /// Where the fish in `bucket` are on the next day, `None` once they die.
+    /// Fish giving birth on their last day still have their babies.
+    fn next_bucket(&self, bucket: usize) -> Option<usize> {
+        match self.mortality_age {
+            None if bucket == 0 => Some(self.cycle - 1),
+            None => Some(bucket - 1),
+            Some(age) if bucket + 1 == age => None,
+            Some(_) => Some(bucket + 1),
+        }
+    }
+
+    fn newborn_bucket(&self) -> usize {
+        match self.mortality_age {
+            None => self.newborn_timer(),
+            Some(_) => 0,
+        }
+    }
+
+    /// move every bucket on a day, `None` if a count gets too big for an i64
+    pub fn step(&self, counts: &[i64]) -> Option<Vec<i64>> {
+        let mut next = vec![0i64; counts.len()];
+        for (bucket, &count) in counts.iter().enumerate() {
+            if let Some(to) = self.next_bucket(bucket) {
+                next[to] = next[to].checked_add(count)?;
+            }
+            if self.gives_birth(bucket) {
+                let babies = count.checked_mul(self.litter as i64)?;
+                let newborn = self.newborn_bucket();
+                next[newborn] = next[newborn].checked_add(babies)?;
+            }
+        }
+        Some(next)
+    }
+
+    /// The same day as a matrix, `next = transition * today`, so that many
+    /// days can be skipped at once by raising it to a power
+    pub fn transition(&self) -> Vec<Vec<u64>> {
+        let size = self.bucket_count();
+        // column `bucket` is where a single fish in that bucket ends up
+        let columns: Vec<Vec<i64>> = (0..size)
+            .map(|bucket| {
+                let mut fish = vec![0; size];
+                fish[bucket] = 1;
+                self.step(&fish).expect("a single fish's babies should fit")
+            })
+            .collect();
+        (0..size)
+            .map(|row| columns.iter().map(|column| column[row] as u64).collect())
+            .collect()
+    }
+}
+
+/// parses `cycle,newborn_delay,litter[,mortality_age]`, like `7,2,1` for the puzzle
+impl FromStr for LifecycleModel {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let values = input
+            .split(',')
+            .map(|value| value.trim().parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| format!("Invalid lifecycle model \"{}\"", input))?;
+        match values[..] {
+            [cycle, delay, litter] => Self::new(cycle, delay, litter as u64, None),
+            [cycle, delay, litter, mortality] => {
+                Self::new(cycle, delay, litter as u64, Some(mortality))
+            }
+            _ => Err(format!(
+                "Expected cycle,newborn_delay,litter[,mortality_age], got \"{}\"",
+                input
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_transition() {
+        let model = LifecycleModel::default();
+        assert_eq!(model, "7,2,1".parse().unwrap());
+        assert_eq!(model.bucket_count(), 9);
+        let rows = model.transition();
+        assert_eq!(rows[6], vec![1, 0, 0, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(rows[8], vec![1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            model.step(&[0, 1, 1, 2, 1, 0, 0, 0, 0]),
+            Some(vec![1, 1, 2, 1, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            model.step(&[1, 1, 2, 1, 0, 0, 0, 0, 0]),
+            Some(vec![1, 2, 1, 0, 0, 0, 1, 0, 1])
+        );
+        assert_eq!(model.step(&[i64::MAX, 0, 0, 0, 0, 0, 0, 1, 0]), None);
+    }
+
+    #[test]
+    fn test_mortality() {
+        // a newborn has babies at ages 9 and 16 then dies at 20, its first
+        // babies have their own at 9 days old
+        let model: LifecycleModel = "7,2,3,20".parse().unwrap();
+        assert_eq!(model.bucket_for_timer(8), Ok(0));
+        assert_eq!(model.bucket_for_timer(3), Ok(5));
+        let mut counts = vec![0; 20];
+        counts[0] = 1;
+        let mut babies = vec![];
+        for day in 1..=20 {
+            counts = model.step(&counts).unwrap();
+            babies.push((day, counts[0]));
+        }
+        let birthdays: Vec<(i64, i64)> = babies.into_iter().filter(|(_, b)| *b > 0).collect();
+        assert_eq!(birthdays, vec![(9, 3), (16, 3), (18, 9)]);
+        // the original fish is gone, the rest are 11, 4 and 2 days old
+        assert_eq!(counts.iter().sum::<i64>(), 15);
+        assert_eq!((counts[11], counts[4], counts[2]), (3, 3, 9));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("7,2".parse::<LifecycleModel>().is_err());
+        assert!("0,2,1".parse::<LifecycleModel>().is_err());
+        assert!("7,2,1,8".parse::<LifecycleModel>().is_err());
+        assert!(LifecycleModel::default().bucket_for_timer(9).is_err());
+    }
+}
@@ -1,14 +1,95 @@
-use std::fs;
 use std::str::FromStr;
+use std::{env, fs};
 
+use big_uint::BigUint;
+use census::Census;
+use lifecycle::LifecycleModel;
+use matrix::{Exact, Matrix, Modular};
+
+mod big_uint;
+mod census;
+mod lifecycle;
+mod matrix;
+
+/// `cargo run -- <day> [modulus]` fast forwards to any day, giving the exact
+/// size or the size modulo `modulus` (a prime like 1000000007 is usual).
+/// `--model=cycle,newborn_delay,litter[,mortality_age]` swaps the puzzle's
+/// lifecycle for another.
+///
+/// `--csv=<days>` prints the counts for every day up to `days` instead, and
+/// `--over=<n>` finds the first day there are more than `n` fish, searching
+/// up to `--limit=<days>` (100000 by default) a day at a time, or by fast
+/// forwarding with `--fast`.
 fn main() {
-    let school: School = fs::read_to_string("input.test").unwrap().parse().unwrap();
+    let (options, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let option = |name: &str| {
+        options
+            .iter()
+            .find_map(|option| option.strip_prefix(&format!("--{}=", name)))
+    };
+    let model = match option("model") {
+        Some(model) => model.parse().unwrap(),
+        None => LifecycleModel::default(),
+    };
+    let input = fs::read_to_string("input.test").unwrap();
+    let school = School::with_model(&input, model).unwrap();
+
+    if let Some(days) = option("csv") {
+        let days = days.parse().expect("days should be a whole number");
+        print!("{}", census::to_csv(&school.census(days).unwrap()));
+        return;
+    }
+    if let Some(n) = option("over") {
+        let n = n.parse().expect("n should be a whole number");
+        let limit = option("limit").map_or(100_000, |limit| {
+            limit.parse().expect("limit should be a whole number")
+        });
+        let search = match options.iter().any(|option| option == "--fast") {
+            true => Search::FastForward,
+            false => Search::Stepping,
+        };
+        match school.first_day_over(n, limit, search).unwrap() {
+            Some(day) => println!("more than {} fish on day {}", n, day),
+            None => println!("never more than {} fish up to day {}", n, limit),
+        }
+        return;
+    }
+
     println!("part 1: {}", school.size_at_time(80));
     println!("part 2: {}", school.size_at_time(256));
+
+    if let Some(day) = args.first() {
+        let day: u64 = day.parse().expect("day should be a whole number");
+        match args.get(1) {
+            Some(modulus) => {
+                let modulus: u64 = modulus.parse().expect("modulus should be a whole number");
+                assert!(modulus > 0, "modulus should be more than 0");
+                println!(
+                    "day {} (mod {}): {}",
+                    day,
+                    modulus,
+                    school.size_at_time_mod(day, modulus)
+                );
+            }
+            None => println!("day {}: {}", day, school.size_at_time_exact(day)),
+        }
+    }
+}
+
+/// how to look for the first day the school gets big enough
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Search {
+    /// a day at a time
+    Stepping,
+    /// double the day until there are enough fish, then binary search back
+    FastForward,
 }
 
 #[derive(Clone)]
 struct School {
+    model: LifecycleModel,
+    /// fish counted by timer, or by age in days if the model has a mortality age
     fish_by_age: Vec<i64>,
 }
 
@@ -16,25 +97,29 @@ impl FromStr for School {
     type Err = String;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let ages: Vec<i64> = input
-            .trim()
-            .split(',')
-            .map(|l| l.parse().unwrap())
-            .collect();
-        Ok(School {
-            fish_by_age: ages.iter().fold(vec![0; 9], |mut by_age, age| {
-                by_age[*age as usize] += 1;
-                by_age
-            }),
-        })
+        School::with_model(input, LifecycleModel::default())
     }
 }
 
 impl School {
+    /// parse the fish timers from the input, to live by `model`
+    fn with_model(input: &str, model: LifecycleModel) -> Result<Self, String> {
+        let mut fish_by_age = vec![0; model.bucket_count()];
+        for timer in input.trim().split(',') {
+            let timer: usize = timer
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid timer \"{}\"", timer))?;
+            fish_by_age[model.bucket_for_timer(timer)?] += 1;
+        }
+        Ok(School { model, fish_by_age })
+    }
+
     fn tick(&mut self) {
-        let giving_birth_count = self.fish_by_age.remove(0);
-        self.fish_by_age.push(giving_birth_count);
-        self.fish_by_age[6] += giving_birth_count;
+        self.fish_by_age = self
+            .model
+            .step(&self.fish_by_age)
+            .expect("too many fish to count in an i64, try fast forwarding");
     }
 
     fn size_at_time(&self, time: i64) -> i64 {
@@ -44,6 +129,118 @@ impl School {
         }
         school.fish_by_age.iter().sum()
     }
+
+    /// the counts for every day from 0 to `days`, stepping a day at a time,
+    /// or an error once there are too many fish to count in an i64
+    fn census(&self, days: usize) -> Result<Vec<Census>, String> {
+        let overflow = |day| format!("Too many fish to count on day {}", day);
+        let mut fish_by_age = self.fish_by_age.clone();
+        let mut series = Vec::with_capacity(days + 1);
+        for day in 0..=days {
+            let total = fish_by_age
+                .iter()
+                .try_fold(0i64, |total, &count| total.checked_add(count))
+                .ok_or_else(|| overflow(day))?;
+            series.push(Census {
+                day,
+                total,
+                fish_by_age: fish_by_age.clone(),
+            });
+            if day < days {
+                fish_by_age = self
+                    .model
+                    .step(&fish_by_age)
+                    .ok_or_else(|| overflow(day + 1))?;
+            }
+        }
+        Ok(series)
+    }
+
+    /// The first day up to `limit` that the school has more than `n` fish.
+    /// Fast forwarding only works when the school can't shrink, which rules
+    /// out models with a mortality age.
+    fn first_day_over(&self, n: u64, limit: u64, search: Search) -> Result<Option<u64>, String> {
+        match search {
+            Search::Stepping => {
+                // exact counts like fast forwarding, a day's matrix at a time
+                let target = BigUint::from(n);
+                let day_matrix = Matrix::from_rows(&Exact, &self.model.transition());
+                let mut fish_by_age: Vec<BigUint> = self
+                    .fish_by_age
+                    .iter()
+                    .map(|&count| BigUint::from(count as u64))
+                    .collect();
+                for day in 0..=limit {
+                    let total = fish_by_age
+                        .iter()
+                        .fold(BigUint::zero(), |total, count| total.add(count));
+                    if total > target {
+                        return Ok(Some(day));
+                    }
+                    if day < limit {
+                        fish_by_age = day_matrix.apply(&Exact, &fish_by_age);
+                    }
+                }
+                Ok(None)
+            }
+            Search::FastForward => {
+                if self.model.has_mortality() {
+                    return Err("Can't fast forward a school that might shrink".to_string());
+                }
+                let target = BigUint::from(n);
+                let over = |day| self.size_at_time_exact(day) > target;
+                if over(0) {
+                    return Ok(Some(0));
+                }
+                // not over at `lo`, keep doubling until over at `hi`
+                let (mut lo, mut hi) = (0, limit.min(1));
+                while !over(hi) {
+                    if hi == limit {
+                        return Ok(None);
+                    }
+                    lo = hi;
+                    hi = hi.saturating_mul(2).min(limit);
+                }
+                while hi - lo > 1 {
+                    let mid = lo + (hi - lo) / 2;
+                    match over(mid) {
+                        true => hi = mid,
+                        false => lo = mid,
+                    }
+                }
+                Ok(Some(hi))
+            }
+        }
+    }
+
+    /// the size after `time` days by raising the transition matrix to the
+    /// power of `time`, which takes O(log time) matrix multiplications
+    fn size_at_time_with<A: matrix::Arithmetic>(&self, arithmetic: &A, time: u64) -> A::Value {
+        let rows = self.model.transition();
+        let ages: Vec<A::Value> = self
+            .fish_by_age
+            .iter()
+            .map(|&count| arithmetic.value(count as u64))
+            .collect();
+        Matrix::from_rows(arithmetic, &rows)
+            .pow(arithmetic, time)
+            .apply(arithmetic, &ages)
+            .iter()
+            .fold(arithmetic.value(0), |total, count| {
+                arithmetic.add(&total, count)
+            })
+    }
+
+    /// the exact size after `time` days, the number of digits grows linearly
+    /// with `time` so this is only quick up to tens of thousands of days
+    fn size_at_time_exact(&self, time: u64) -> BigUint {
+        self.size_at_time_with(&Exact, time)
+    }
+
+    /// the size after `time` days modulo `modulus`, quick for any `time`
+    fn size_at_time_mod(&self, time: u64, modulus: u64) -> u64 {
+        self.size_at_time_with(&Modular(modulus), time)
+    }
 }
 
 #[cfg(test)]
@@ -79,4 +276,107 @@ mod tests {
         let school: School = fs::read_to_string("input").unwrap().parse().unwrap();
         assert_eq!(school.size_at_time(256), 1629570219571);
     }
+
+    #[test]
+    fn test_models() {
+        let input = fs::read_to_string("input.test").unwrap();
+        let twins = School::with_model(&input, "7,2,2".parse().unwrap()).unwrap();
+        let mortal = School::with_model(&input, "7,2,1,30".parse().unwrap()).unwrap();
+        let puzzle: School = input.parse().unwrap();
+        assert_eq!(mortal.fish_by_age.len(), 30);
+        assert_eq!(mortal.fish_by_age[4..8], [1, 2, 1, 1]);
+        for time in [0, 18, 80] {
+            assert!(twins.size_at_time(time) >= puzzle.size_at_time(time));
+            assert!(mortal.size_at_time(time) <= puzzle.size_at_time(time));
+        }
+        // nobody has died by day 18, all the first fish are younger than 12
+        assert_eq!(mortal.size_at_time(18), 26);
+        assert_eq!(twins.size_at_time(2), 5 + 2);
+        for school in [twins, mortal] {
+            for time in [0, 1, 80, 200] {
+                assert_eq!(
+                    school.size_at_time_mod(time as u64, 1_000_000_007),
+                    (school.size_at_time(time) % 1_000_000_007) as u64
+                );
+            }
+        }
+        assert!(School::with_model("3,9", LifecycleModel::default()).is_err());
+        assert!("3,x".parse::<School>().is_err());
+    }
+
+    #[test]
+    fn test_census() {
+        let school: School = fs::read_to_string("input.test").unwrap().parse().unwrap();
+        let series = school.census(18).unwrap();
+        assert_eq!(series.len(), 19);
+        assert_eq!(series[0].total, 5);
+        assert_eq!(series[2].fish_by_age, vec![1, 2, 1, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(series[18].total, 26);
+        let error = school.census(1000).unwrap_err();
+        assert!(error.starts_with("Too many fish to count on day"));
+        let csv = census::to_csv(&school.census(1).unwrap());
+        assert_eq!(
+            csv,
+            "day,total,age_0,age_1,age_2,age_3,age_4,age_5,age_6,age_7,age_8\n\
+             0,5,0,1,1,2,1,0,0,0,0\n\
+             1,5,1,1,2,1,0,0,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_first_day_over() {
+        let school: School = fs::read_to_string("input.test").unwrap().parse().unwrap();
+        for search in [Search::Stepping, Search::FastForward] {
+            assert_eq!(school.first_day_over(4, 1000, search), Ok(Some(0)));
+            assert_eq!(school.first_day_over(5, 1000, search), Ok(Some(2)));
+            assert_eq!(school.first_day_over(26, 1000, search), Ok(Some(19)));
+            assert_eq!(school.first_day_over(5933, 1000, search), Ok(Some(80)));
+            assert_eq!(school.first_day_over(5934, 1000, search), Ok(Some(81)));
+            assert_eq!(school.first_day_over(5934, 80, search), Ok(None));
+            assert_eq!(school.first_day_over(5, 0, search), Ok(None));
+        }
+        let big = school
+            .first_day_over(u64::MAX - 1, 10_000, Search::FastForward)
+            .unwrap()
+            .unwrap();
+        assert!(school.size_at_time_exact(big - 1) < BigUint::from(u64::MAX));
+        assert_eq!(
+            school.first_day_over(u64::MAX - 1, 10_000, Search::Stepping),
+            Ok(Some(big))
+        );
+
+        let input = fs::read_to_string("input.test").unwrap();
+        let mortal = School::with_model(&input, "7,2,1,30".parse().unwrap()).unwrap();
+        assert!(mortal.first_day_over(10, 100, Search::FastForward).is_err());
+        assert_eq!(mortal.first_day_over(5, 100, Search::Stepping), Ok(Some(2)));
+    }
+
+    #[test]
+    fn test_fast_forward_matches_stepping() {
+        let school: School = fs::read_to_string("input").unwrap().parse().unwrap();
+        for time in [0, 1, 18, 80, 256] {
+            let stepped = school.size_at_time(time as i64);
+            assert_eq!(
+                school.size_at_time_exact(time).to_string(),
+                stepped.to_string()
+            );
+            assert_eq!(
+                school.size_at_time_mod(time, 1_000_000_007),
+                (stepped % 1_000_000_007) as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_fast_forward_past_overflow() {
+        let school: School = fs::read_to_string("input.test").unwrap().parse().unwrap();
+        let exact = school.size_at_time_exact(1000).to_string();
+        assert!(exact.len() > 30);
+        let modulus = 998_244_353;
+        let remainder = exact.bytes().fold(0, |total, digit| {
+            (total * 10 + (digit - b'0') as u64) % modulus
+        });
+        assert_eq!(school.size_at_time_mod(1000, modulus), remainder);
+        assert!(school.size_at_time_mod(1_000_000_000_000, modulus) < modulus);
+    }
 }
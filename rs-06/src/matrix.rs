@@ -0,0 +1,146 @@
+use crate::big_uint::BigUint;
+
+/// The numbers a matrix is made of. Modular counts need to carry their
+/// modulus around, so values are built and combined through the arithmetic
+/// rather than on their own.
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn value(&self, value: u64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// exact counts, however big they get
+pub struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = BigUint;
+
+    fn value(&self, value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a.add(b)
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a.mul(b)
+    }
+}
+
+/// counts modulo a (usually prime) modulus
+pub struct Modular(pub u64);
+
+impl Arithmetic for Modular {
+    type Value = u64;
+
+    fn value(&self, value: u64) -> u64 {
+        value % self.0
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0 as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.0 as u128) as u64
+    }
+}
+
+/// a square matrix, stored row by row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Matrix<T> {
+    pub fn from_rows<A: Arithmetic<Value = T>>(arithmetic: &A, rows: &[Vec<u64>]) -> Self {
+        Self {
+            size: rows.len(),
+            cells: rows
+                .iter()
+                .flatten()
+                .map(|&value| arithmetic.value(value))
+                .collect(),
+        }
+    }
+
+    pub fn identity<A: Arithmetic<Value = T>>(arithmetic: &A, size: usize) -> Self {
+        let rows: Vec<Vec<u64>> = (0..size)
+            .map(|row| (0..size).map(|col| (row == col) as u64).collect())
+            .collect();
+        Self::from_rows(arithmetic, &rows)
+    }
+
+    pub fn mul<A: Arithmetic<Value = T>>(&self, arithmetic: &A, other: &Self) -> Self {
+        let size = self.size;
+        let mut cells = Vec::with_capacity(size * size);
+        for row in 0..size {
+            for col in 0..size {
+                let mut total = arithmetic.value(0);
+                for k in 0..size {
+                    let product =
+                        arithmetic.mul(&self.cells[row * size + k], &other.cells[k * size + col]);
+                    total = arithmetic.add(&total, &product);
+                }
+                cells.push(total);
+            }
+        }
+        Self { size, cells }
+    }
+
+    /// raise to `exponent` by repeated squaring, O(log exponent) multiplications
+    pub fn pow<A: Arithmetic<Value = T>>(&self, arithmetic: &A, mut exponent: u64) -> Self {
+        let mut result = Self::identity(arithmetic, self.size);
+        let mut square = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(arithmetic, &square);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square.mul(arithmetic, &square);
+            }
+        }
+        result
+    }
+
+    /// multiply by a column vector
+    pub fn apply<A: Arithmetic<Value = T>>(&self, arithmetic: &A, vector: &[T]) -> Vec<T> {
+        (0..self.size)
+            .map(|row| {
+                (0..self.size).fold(arithmetic.value(0), |total, col| {
+                    let product = arithmetic.mul(&self.cells[row * self.size + col], &vector[col]);
+                    arithmetic.add(&total, &product)
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow() {
+        // fibonacci numbers, F(n + 1) sits in the top left
+        let fib = Matrix::from_rows(&Modular(u64::MAX), &[vec![1, 1], vec![1, 0]]);
+        assert_eq!(
+            fib.pow(&Modular(u64::MAX), 0),
+            Matrix::identity(&Modular(u64::MAX), 2)
+        );
+        assert_eq!(fib.pow(&Modular(u64::MAX), 10).cells[0], 89);
+        assert_eq!(fib.pow(&Modular(1000), 10).cells[0], 89);
+        assert_eq!(fib.pow(&Modular(10), 10).cells[0], 9);
+
+        let fib = Matrix::from_rows(&Exact, &[vec![1, 1], vec![1, 0]]);
+        assert_eq!(
+            fib.pow(&Exact, 100).cells[1].to_string(),
+            "354224848179261915075"
+        );
+    }
+}
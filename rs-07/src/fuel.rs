@@ -0,0 +1,192 @@
+/// How much fuel a crab burns to move `distance` steps.
+///
+/// The solver relies on the cost being convex and never going down as the
+/// distance grows, which holds for all the built in costs.
+pub trait FuelCost {
+    fn cost(&self, distance: i64) -> i64;
+
+    /// `Some((a, b, divisor))` if the cost is `(a * d * d + b * d) / divisor`,
+    /// which lets a whole group of crabs be costed from prefix sums
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        None
+    }
+}
+
+impl<F: Fn(i64) -> i64> FuelCost for F {
+    fn cost(&self, distance: i64) -> i64 {
+        self(distance)
+    }
+}
+
+impl FuelCost for Box<dyn FuelCost> {
+    fn cost(&self, distance: i64) -> i64 {
+        (**self).cost(distance)
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        (**self).polynomial()
+    }
+}
+
+/// look up a built in cost by name
+pub fn from_name(name: &str) -> Result<Box<dyn FuelCost>, String> {
+    match name {
+        "linear" => Ok(Box::new(Linear)),
+        "triangular" => Ok(Box::new(Triangular)),
+        "quadratic" => Ok(Box::new(Quadratic)),
+        _ => Err(format!("Unknown fuel cost \"{}\"", name)),
+    }
+}
+
+/// one fuel per step, part 1
+pub struct Linear;
+
+impl FuelCost for Linear {
+    fn cost(&self, distance: i64) -> i64 {
+        distance
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((0, 1, 1))
+    }
+}
+
+/// each step costs one more than the last, part 2
+pub struct Triangular;
+
+impl FuelCost for Triangular {
+    fn cost(&self, distance: i64) -> i64 {
+        distance * (distance + 1) / 2
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((1, 1, 2))
+    }
+}
+
+/// the square of the distance
+pub struct Quadratic;
+
+impl FuelCost for Quadratic {
+    fn cost(&self, distance: i64) -> i64 {
+        distance * distance
+    }
+
+    fn polynomial(&self) -> Option<(i64, i64, i64)> {
+        Some((1, 0, 1))
+    }
+}
+
+/// where the crabs should line up and what it costs them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alignment {
+    pub position: i32,
+    pub fuel: i64,
+}
+
+pub fn total_fuel(crabs: &[i32], position: i32, cost: &impl FuelCost) -> i64 {
+    crabs
+        .iter()
+        .map(|&crab| cost.cost((crab - position).abs() as i64))
+        .sum()
+}
+
+/// The cheapest position, the leftmost if several cost the same, or `None`
+/// if there are no crabs.
+pub fn align(crabs: &[i32], cost: &impl FuelCost) -> Option<Alignment> {
+    let (lo, hi) = (*crabs.iter().min()?, *crabs.iter().max()?);
+    Some(minimize(lo, hi, |position| {
+        total_fuel(crabs, position, cost)
+    }))
+}
+
+/// The leftmost minimum of a convex `fuel` between `lo` and `hi`. A binary
+/// search for where it stops going down finds it after O(log range)
+/// evaluations.
+pub fn minimize(mut lo: i32, mut hi: i32, fuel: impl Fn(i32) -> i64) -> Alignment {
+    // the first position where moving one to the right doesn't help
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if fuel(mid + 1) < fuel(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Alignment {
+        position: lo,
+        fuel: fuel(lo),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_input;
+
+    fn brute_force(crabs: &[i32], cost: &impl FuelCost) -> Alignment {
+        let (min, max) = (*crabs.iter().min().unwrap(), *crabs.iter().max().unwrap());
+        (min..=max)
+            .map(|position| Alignment {
+                position,
+                fuel: total_fuel(crabs, position, cost),
+            })
+            .min_by_key(|alignment| alignment.fuel)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sample() {
+        let crabs = get_input("input.test");
+        assert_eq!(
+            align(&crabs, &Linear),
+            Some(Alignment {
+                position: 2,
+                fuel: 37
+            })
+        );
+        assert_eq!(
+            align(&crabs, &Triangular),
+            Some(Alignment {
+                position: 5,
+                fuel: 168
+            })
+        );
+        assert_eq!(align(&[], &Linear), None);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for filename in ["input.test", "input"] {
+            let crabs = get_input(filename);
+            let cube = |distance: i64| distance * distance * distance;
+            assert_eq!(align(&crabs, &Linear), Some(brute_force(&crabs, &Linear)));
+            assert_eq!(
+                align(&crabs, &Triangular),
+                Some(brute_force(&crabs, &Triangular))
+            );
+            assert_eq!(
+                align(&crabs, &Quadratic),
+                Some(brute_force(&crabs, &Quadratic))
+            );
+            assert_eq!(align(&crabs, &cube), Some(brute_force(&crabs, &cube)));
+        }
+    }
+
+    #[test]
+    fn test_optimum_at_the_edge() {
+        // the old brute force never tried the furthest crab's position
+        let mut crabs = vec![10; 20];
+        crabs.push(0);
+        assert_eq!(
+            align(&crabs, &Triangular),
+            Some(Alignment {
+                position: 10,
+                fuel: 55
+            })
+        );
+        assert_eq!(align(&[3, 3], &Quadratic).unwrap().position, 3);
+        assert_eq!(from_name("quadratic").unwrap().cost(4), 16);
+        assert!(from_name("cubic").is_err());
+    }
+}
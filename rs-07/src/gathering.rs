@@ -0,0 +1,214 @@
+use crate::fuel::{minimize, total_fuel, Alignment, FuelCost};
+
+/// Crabs split between several gathering positions
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gathering {
+    /// the gathering positions, left to right
+    pub positions: Vec<i32>,
+    pub fuel: i64,
+    /// for each crab in input order, the index of the position it goes to
+    pub assignments: Vec<usize>,
+}
+
+/// The crabs sorted, with prefix sums of their positions and squared
+/// positions so a run of them can be costed without visiting each one
+struct Sorted {
+    crabs: Vec<i32>,
+    sums: Vec<i64>,
+    squares: Vec<i64>,
+}
+
+impl Sorted {
+    fn new(crabs: Vec<i32>) -> Self {
+        let mut sums = vec![0];
+        let mut squares = vec![0];
+        for &crab in &crabs {
+            let crab = crab as i64;
+            sums.push(sums.last().unwrap() + crab);
+            squares.push(squares.last().unwrap() + crab * crab);
+        }
+        Self {
+            crabs,
+            sums,
+            squares,
+        }
+    }
+
+    /// fuel for the crabs in `lo..hi` to get to `position`
+    fn fuel(&self, lo: usize, hi: usize, position: i32, cost: &impl FuelCost) -> i64 {
+        let (a, b, divisor) = match cost.polynomial() {
+            Some(polynomial) => polynomial,
+            None => return total_fuel(&self.crabs[lo..hi], position, cost),
+        };
+        // crabs in `lo..mid` are left of (or on) the position, the rest right
+        let mid = lo + self.crabs[lo..hi].partition_point(|&crab| crab <= position);
+        let p = position as i64;
+        let (left, right) = ((mid - lo) as i64, (hi - mid) as i64);
+        let (left_sum, right_sum) = (
+            self.sums[mid] - self.sums[lo],
+            self.sums[hi] - self.sums[mid],
+        );
+        let squares = self.squares[hi] - self.squares[lo];
+        let distances = (left * p - left_sum) + (right_sum - right * p);
+        let squared_distances = squares - 2 * p * (left_sum + right_sum) + (left + right) * p * p;
+        (a * squared_distances + b * distances) / divisor
+    }
+
+    /// the cheapest place for the crabs in `lo..hi` to meet
+    fn best(&self, lo: usize, hi: usize, cost: &impl FuelCost) -> Alignment {
+        minimize(self.crabs[lo], self.crabs[hi - 1], |position| {
+            self.fuel(lo, hi, position, cost)
+        })
+    }
+}
+
+/// Split the crabs between `k` gathering positions using as little fuel as
+/// possible, `None` if there are no crabs, `k` is 0 or there are fewer
+/// crabs than positions.
+///
+/// Each crab goes to its closest position, so once sorted every position
+/// takes a run of neighbouring crabs. Dynamic programming over where the
+/// runs split finds the best split in O(k * n^2) steps, each costing a run
+/// from prefix sums for the built in costs (other costs visit every crab).
+pub fn gather(crabs: &[i32], k: usize, cost: &impl FuelCost) -> Option<Gathering> {
+    let n = crabs.len();
+    if k == 0 || k > n {
+        return None;
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| crabs[i]);
+    let sorted = Sorted::new(order.iter().map(|&i| crabs[i]).collect());
+
+    // best[lo][hi] is the best meeting place for the run `lo..hi`
+    let best: Vec<Vec<Option<Alignment>>> = (0..n)
+        .map(|lo| {
+            (0..=n)
+                .map(|hi| (hi > lo).then(|| sorted.best(lo, hi, cost)))
+                .collect()
+        })
+        .collect();
+
+    // fuel[j][i] is the least fuel for the first i crabs split into j runs,
+    // and start[j][i] where the last of those runs starts
+    let mut fuel = vec![vec![i64::MAX; n + 1]; k + 1];
+    let mut start = vec![vec![0; n + 1]; k + 1];
+    fuel[0][0] = 0;
+    for j in 1..=k {
+        for i in j..=n {
+            for m in j - 1..i {
+                if fuel[j - 1][m] == i64::MAX {
+                    continue;
+                }
+                let total = fuel[j - 1][m] + best[m][i].unwrap().fuel;
+                if total < fuel[j][i] {
+                    fuel[j][i] = total;
+                    start[j][i] = m;
+                }
+            }
+        }
+    }
+
+    let mut runs = vec![];
+    let mut hi = n;
+    for j in (1..=k).rev() {
+        let lo = start[j][hi];
+        runs.push((lo, hi));
+        hi = lo;
+    }
+    runs.reverse();
+
+    let mut assignments = vec![0; n];
+    for (group, &(lo, hi)) in runs.iter().enumerate() {
+        for &crab in &order[lo..hi] {
+            assignments[crab] = group;
+        }
+    }
+    Some(Gathering {
+        positions: runs
+            .iter()
+            .map(|&(lo, hi)| best[lo][hi].unwrap().position)
+            .collect(),
+        fuel: fuel[k][n],
+        assignments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuel::{align, Linear, Quadratic, Triangular};
+    use crate::get_input;
+
+    #[test]
+    fn test_prefix_sums_match_direct() {
+        let crabs = get_input("input.test");
+        let mut sorted_crabs = crabs.clone();
+        sorted_crabs.sort_unstable();
+        let sorted = Sorted::new(sorted_crabs.clone());
+        for position in -2..20 {
+            for (lo, hi) in [(0, 10), (2, 7), (9, 10)] {
+                let slice = &sorted_crabs[lo..hi];
+                assert_eq!(
+                    sorted.fuel(lo, hi, position, &Linear),
+                    total_fuel(slice, position, &Linear)
+                );
+                assert_eq!(
+                    sorted.fuel(lo, hi, position, &Triangular),
+                    total_fuel(slice, position, &Triangular)
+                );
+                assert_eq!(
+                    sorted.fuel(lo, hi, position, &Quadratic),
+                    total_fuel(slice, position, &Quadratic)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_position_is_align() {
+        let crabs = get_input("input.test");
+        let gathering = gather(&crabs, 1, &Triangular).unwrap();
+        let alignment = align(&crabs, &Triangular).unwrap();
+        assert_eq!(gathering.positions, vec![alignment.position]);
+        assert_eq!(gathering.fuel, alignment.fuel);
+        assert_eq!(gathering.assignments, vec![0; crabs.len()]);
+    }
+
+    #[test]
+    fn test_sample() {
+        // 0 1 1 2 2 2 4 7 | 14 16
+        let crabs = get_input("input.test");
+        let gathering = gather(&crabs, 2, &Linear).unwrap();
+        assert_eq!(gathering.positions, vec![2, 14]);
+        assert_eq!(gathering.fuel, 11 + 2);
+        assert_eq!(gathering.assignments, vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let gathering = gather(&crabs, 10, &Linear).unwrap();
+        assert_eq!(gathering.fuel, 0);
+        assert!(gather(&crabs, 11, &Linear).is_none());
+        assert!(gather(&crabs, 0, &Linear).is_none());
+    }
+
+    #[test]
+    fn test_matches_every_split() {
+        // with two positions, try every place the sorted crabs could split
+        let crabs = get_input("input.test");
+        let mut sorted = crabs.clone();
+        sorted.sort_unstable();
+        let cube = |distance: i64| distance * distance * distance;
+        let best_split = |cost: &dyn Fn(&[i32]) -> i64| {
+            (1..sorted.len())
+                .map(|split| cost(&sorted[..split]) + cost(&sorted[split..]))
+                .min()
+                .unwrap()
+        };
+        assert_eq!(
+            gather(&crabs, 2, &Triangular).unwrap().fuel,
+            best_split(&|run| align(run, &Triangular).unwrap().fuel)
+        );
+        assert_eq!(
+            gather(&crabs, 2, &cube).unwrap().fuel,
+            best_split(&|run| align(run, &cube).unwrap().fuel)
+        );
+    }
+}
@@ -1,22 +1,80 @@
-use std::fs;
+use std::{env, fs};
 
+use fuel::{align, FuelCost, Linear, Triangular};
+
+mod fuel;
+mod gathering;
+mod plane;
+
+/// `cargo run -- <cost> [k]` also aligns the crabs with a named fuel cost,
+/// or splits them between `k` positions.
+///
+/// `cargo run -- 2d <filename> [cost]` aligns crabs given as `x,y` pairs,
+/// on the grid with the cost (linear by default) paid per axis, and in
+/// straight lines with the cost paid on the whole distance.
 fn main() {
     println!("part 1: {}", part_1("input"));
     println!("part 2: {}", part_2("input"));
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("2d") {
+        let points = get_points(args.get(1).expect("2d needs a filename"));
+        let name = args.get(2).map_or("linear", String::as_str);
+        let cost = fuel::from_name(name).unwrap();
+        let grid = plane::align_manhattan(&points, &cost).unwrap();
+        println!(
+            "grid ({}): point {:?}, fuel {}",
+            name, grid.point, grid.fuel
+        );
+        let straight = plane::align_euclidean(&points, &cost, 1e-9)
+            .unwrap()
+            .unwrap();
+        println!(
+            "straight lines ({}): point ({:.3}, {:.3}), fuel {:.3}",
+            name, straight.point.0, straight.point.1, straight.fuel
+        );
+    } else if let Some(name) = args.first() {
+        let cost = fuel::from_name(name).unwrap();
+        let crabs = get_input("input");
+        match args.get(1) {
+            Some(k) => {
+                let k = k.parse().expect("k should be a whole number");
+                let gathering = gathering::gather(&crabs, k, &cost).unwrap();
+                println!("{}: fuel {}", name, gathering.fuel);
+                for (group, position) in gathering.positions.iter().enumerate() {
+                    let count = gathering
+                        .assignments
+                        .iter()
+                        .filter(|&&assigned| assigned == group)
+                        .count();
+                    println!("  {} crabs to position {}", count, position);
+                }
+            }
+            None => {
+                let alignment = align(&crabs, &cost).unwrap();
+                println!(
+                    "{}: position {}, fuel {}",
+                    name, alignment.position, alignment.fuel
+                );
+            }
+        }
+    }
+}
+
+fn part_1(filename: &str) -> i64 {
+    solve(filename, &Linear)
 }
 
-fn part_1(filename: &str) -> i32 {
-    let input = get_input(filename);
-    let median = median(&input);
-    let sum: i32 = input.iter().map(|x| (x - median).abs()).sum();
-    sum
+fn part_2(filename: &str) -> i64 {
+    solve(filename, &Triangular)
 }
 
-fn median(numbers: &[i32]) -> i32 {
-    let mut numbers = numbers.to_owned();
-    numbers.sort_unstable();
-    let mid = numbers.len() / 2;
-    numbers[mid]
+fn solve(filename: &str, cost: &impl FuelCost) -> i64 {
+    align(&get_input(filename), cost).unwrap().fuel
+}
+
+fn get_points(filename: &str) -> Vec<plane::Point> {
+    plane::parse_points(&fs::read_to_string(filename).unwrap()).unwrap()
 }
 
 fn get_input(filename: &str) -> Vec<i32> {
@@ -28,23 +86,6 @@ fn get_input(filename: &str) -> Vec<i32> {
         .collect()
 }
 
-fn part_2(filename: &str) -> i32 {
-    let input = get_input(filename);
-    let max_position = input.iter().max().unwrap();
-    let mut fuel_costs = vec![];
-    for current_position in 0..*max_position {
-        let sum: i32 = input
-            .iter()
-            .map(|x| {
-                let distance = (x - current_position).abs();
-                (distance * (distance + 1)) / 2
-            })
-            .sum();
-        fuel_costs.push(sum);
-    }
-    *fuel_costs.iter().min().unwrap()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,216 @@
+use crate::fuel::{align, FuelCost};
+
+pub type Point = (i32, i32);
+
+/// parses `x,y` pairs, separated by newlines or spaces
+pub fn parse_points(input: &str) -> Result<Vec<Point>, String> {
+    input
+        .split_whitespace()
+        .map(|pair| {
+            let coordinates = pair
+                .split(',')
+                .map(|value| value.parse::<i32>())
+                .collect::<Result<Vec<i32>, _>>()
+                .map_err(|_| format!("Invalid point \"{}\"", pair))?;
+            match coordinates[..] {
+                [x, y] => Ok((x, y)),
+                _ => Err(format!("Expected x,y, got \"{}\"", pair)),
+            }
+        })
+        .collect()
+}
+
+/// where the crabs should meet on the grid and what it costs them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridAlignment {
+    pub point: Point,
+    pub fuel: i64,
+}
+
+/// Meet at a grid point with each axis paid for separately, so a crab
+/// moving `dx` across and `dy` down burns `cost(dx) + cost(dy)`. With
+/// `Linear` that's the Manhattan distance, and the best point is the median
+/// of each coordinate. Either way the axes don't affect each other, so each
+/// is aligned on its own.
+pub fn align_manhattan(points: &[Point], cost: &impl FuelCost) -> Option<GridAlignment> {
+    let xs: Vec<i32> = points.iter().map(|point| point.0).collect();
+    let ys: Vec<i32> = points.iter().map(|point| point.1).collect();
+    let (x, y) = (align(&xs, cost)?, align(&ys, cost)?);
+    Some(GridAlignment {
+        point: (x.position, y.position),
+        fuel: x.fuel + y.fuel,
+    })
+}
+
+/// where the crabs should meet when they can move in a straight line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EuclideanAlignment {
+    pub point: (f64, f64),
+    pub fuel: f64,
+}
+
+/// the cost of moving a real `distance` with polynomial cost `(a, b, divisor)`
+fn real_cost((a, b, divisor): (i64, i64, i64), distance: f64) -> f64 {
+    (a as f64 * distance * distance + b as f64 * distance) / divisor as f64
+}
+
+fn euclidean_fuel(points: &[Point], polynomial: (i64, i64, i64), (x, y): (f64, f64)) -> f64 {
+    points
+        .iter()
+        .map(|&(px, py)| real_cost(polynomial, (px as f64 - x).hypot(py as f64 - y)))
+        .sum()
+}
+
+/// The point with the least total fuel when every crab moves in a straight
+/// line, paying `cost` on the (not rounded) distance. Only costs with a
+/// `polynomial` form can be paid on a fractional distance, others are an
+/// error. `None` if there are no crabs.
+///
+/// With `Linear` this is the geometric median, which has no closed form, so
+/// this uses Weiszfeld's method: start at the centroid and keep moving to the
+/// average of the crabs weighted by how close they are, until it moves less
+/// than `tolerance`. A cost of `(a * d * d + b * d) / divisor` weights each
+/// crab by `2a + b / d` instead. The iteration can't handle the best point
+/// landing on a crab, so the crabs' own points are checked too.
+pub fn align_euclidean(
+    points: &[Point],
+    cost: &impl FuelCost,
+    tolerance: f64,
+) -> Result<Option<EuclideanAlignment>, String> {
+    let polynomial = cost
+        .polynomial()
+        .ok_or("Straight line fuel needs a cost like (a * d * d + b * d) / divisor")?;
+    if points.is_empty() {
+        return Ok(None);
+    }
+    let (a, b) = (polynomial.0 as f64, polynomial.1 as f64);
+    let count = points.len() as f64;
+    let mut current = (
+        points.iter().map(|point| point.0 as f64).sum::<f64>() / count,
+        points.iter().map(|point| point.1 as f64).sum::<f64>() / count,
+    );
+    for _ in 0..10_000 {
+        let (mut x, mut y, mut weights) = (0.0, 0.0, 0.0);
+        for &(px, py) in points {
+            let distance = (px as f64 - current.0).hypot(py as f64 - current.1);
+            let weight = match distance < f64::EPSILON {
+                true => 2.0 * a,
+                false => 2.0 * a + b / distance,
+            };
+            x += px as f64 * weight;
+            y += py as f64 * weight;
+            weights += weight;
+        }
+        if weights == 0.0 {
+            break;
+        }
+        let next = (x / weights, y / weights);
+        let moved = (next.0 - current.0).hypot(next.1 - current.1);
+        current = next;
+        if moved < tolerance {
+            break;
+        }
+    }
+    let crabs = points.iter().map(|&(x, y)| (x as f64, y as f64));
+    Ok(std::iter::once(current)
+        .chain(crabs)
+        .map(|point| EuclideanAlignment {
+            point,
+            fuel: euclidean_fuel(points, polynomial, point),
+        })
+        .min_by(|a, b| a.fuel.total_cmp(&b.fuel)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuel::{Linear, Quadratic, Triangular};
+    use crate::get_points;
+
+    #[test]
+    fn test_parse_points() {
+        assert_eq!(
+            parse_points("0,0\n4,0 -1,2\n"),
+            Ok(vec![(0, 0), (4, 0), (-1, 2)])
+        );
+        assert!(parse_points("1,2,3").is_err());
+        assert!(parse_points("1,x").is_err());
+    }
+
+    #[test]
+    fn test_manhattan() {
+        let points = get_points("input.2d.test");
+        assert_eq!(
+            align_manhattan(&points, &Linear),
+            Some(GridAlignment {
+                point: (1, 1),
+                fuel: 16
+            })
+        );
+        // brute force every grid point with the cost applied per axis
+        let brute_force = (0..=4)
+            .flat_map(|x| (0..=4).map(move |y| (x, y)))
+            .map(|(x, y)| {
+                points
+                    .iter()
+                    .map(|&(px, py)| {
+                        Triangular.cost((px - x).abs() as i64)
+                            + Triangular.cost((py - y).abs() as i64)
+                    })
+                    .sum::<i64>()
+            })
+            .min();
+        assert_eq!(
+            align_manhattan(&points, &Triangular).map(|alignment| alignment.fuel),
+            brute_force
+        );
+        assert_eq!(align_manhattan(&[], &Linear), None);
+    }
+
+    #[test]
+    fn test_euclidean() {
+        let square = vec![(0, 0), (4, 0), (0, 4), (4, 4)];
+        let alignment = align_euclidean(&square, &Linear, 1e-9).unwrap().unwrap();
+        assert!((alignment.point.0 - 2.0).abs() < 1e-6);
+        assert!((alignment.point.1 - 2.0).abs() < 1e-6);
+        assert!((alignment.fuel - 4.0 * 8f64.sqrt()).abs() < 1e-6);
+
+        // the middle crab of three in a row is the median
+        let line = vec![(0, 0), (1, 0), (10, 0)];
+        let alignment = align_euclidean(&line, &Linear, 1e-9).unwrap().unwrap();
+        assert!((alignment.fuel - 10.0).abs() < 1e-6);
+
+        // nowhere nearby does better
+        let points = get_points("input.2d.test");
+        let alignment = align_euclidean(&points, &Linear, 1e-9).unwrap().unwrap();
+        for (dx, dy) in [(0.01, 0.0), (-0.01, 0.0), (0.0, 0.01), (0.0, -0.01)] {
+            let (x, y) = alignment.point;
+            let nearby = euclidean_fuel(&points, (0, 1, 1), (x + dx, y + dy));
+            assert!(alignment.fuel <= nearby);
+        }
+        assert_eq!(align_euclidean(&[], &Linear, 1e-9), Ok(None));
+    }
+
+    #[test]
+    fn test_euclidean_costs() {
+        // squared distances are cheapest from the centroid
+        let line = vec![(0, 0), (1, 0), (10, 0)];
+        let alignment = align_euclidean(&line, &Quadratic, 1e-9).unwrap().unwrap();
+        assert!((alignment.point.0 - 11.0 / 3.0).abs() < 1e-6);
+        assert!(alignment.point.1.abs() < 1e-6);
+
+        // part 2's cost lands between the median and the centroid
+        let points = get_points("input.2d.test");
+        let alignment = align_euclidean(&points, &Triangular, 1e-9)
+            .unwrap()
+            .unwrap();
+        for (dx, dy) in [(0.01, 0.0), (-0.01, 0.0), (0.0, 0.01), (0.0, -0.01)] {
+            let (x, y) = alignment.point;
+            let nearby = euclidean_fuel(&points, (1, 1, 2), (x + dx, y + dy));
+            assert!(alignment.fuel <= nearby);
+        }
+
+        let cube = |distance: i64| distance * distance * distance;
+        assert!(align_euclidean(&points, &cube, 1e-9).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use crate::layout::DECIMAL;
+use crate::segments::Segments;
+
+/// each digit's segments on a correctly wired display
+const DIGITS: [Segments; 10] = {
+    let mut digits = [Segments::EMPTY; 10];
+    let mut i = 0;
+    while i < digits.len() {
+        digits[i] = Segments::from_letters(DECIMAL[i].1);
+        i += 1;
+    }
+    digits
+};
+
+/// for each of the seven wires (or segments), which of `digits` light it,
+/// one bit per digit
+fn lit_by(digits: &[Segments; 10]) -> [u16; 7] {
+    let mut lit_by = [0; 7];
+    for (wire, lit) in lit_by.iter_mut().enumerate() {
+        for (digit, segments) in digits.iter().enumerate() {
+            if segments.bits() & 1 << wire != 0 {
+                *lit |= 1 << digit;
+            }
+        }
+    }
+    lit_by
+}
+
+/// Whether a single wiring shows each of `digits` as its glyph. A wire can
+/// only drive the segment lit by exactly the same digits, so every wire
+/// needs a segment to match and no two wires can match the same one.
+fn consistent(digits: &[Segments; 10]) -> bool {
+    let (wires, segments) = (lit_by(digits), lit_by(&DIGITS));
+    wires
+        .iter()
+        .enumerate()
+        .all(|(i, wire)| segments.contains(wire) && !wires[..i].contains(wire))
+}
+
+/// An entry for the puzzle's display packed into a fixed size, with no
+/// heap allocations to read or decode it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PackedEntry {
+    pub patterns: [Segments; 10],
+    pub output: [Segments; 4],
+}
+
+impl PackedEntry {
+    /// Work out which pattern is which digit with subset tests against the
+    /// 1 and 4, then read the output. `None` if the patterns aren't the ten
+    /// digits under one wiring, which `Layout::solve` can explain.
+    pub fn decode(&self) -> Option<u32> {
+        let mut digits = [Segments::EMPTY; 10];
+        for &pattern in &self.patterns {
+            match pattern.len() {
+                2 => digits[1] = pattern,
+                3 => digits[7] = pattern,
+                4 => digits[4] = pattern,
+                7 => digits[8] = pattern,
+                _ => (),
+            }
+        }
+        let (one, four) = (digits[1], digits[4]);
+        if one.is_empty() || four.is_empty() {
+            return None;
+        }
+        for &pattern in &self.patterns {
+            let digit = match pattern.len() {
+                // 9 covers the 4, 0 only the 1 and 6 neither
+                6 if pattern.is_superset(four) => 9,
+                6 if pattern.is_superset(one) => 0,
+                6 => 6,
+                // 3 covers the 1, 5 shares three segments with the 4 and 2 two
+                5 if pattern.is_superset(one) => 3,
+                5 if pattern.intersection(four).len() == 3 => 5,
+                5 => 2,
+                _ => continue,
+            };
+            digits[digit] = pattern;
+        }
+        // the guesses above only look at a few segments, so check they add
+        // up to one wiring
+        if !consistent(&digits) {
+            return None;
+        }
+        self.output.iter().try_fold(0, |number, pattern| {
+            let digit = digits.iter().position(|digit| digit == pattern)?;
+            Some(number * 10 + digit as u32)
+        })
+    }
+}
+
+/// parses `<ten patterns> | <four patterns>`
+impl FromStr for PackedEntry {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (patterns, output) = input
+            .split_once(" | ")
+            .ok_or_else(|| format!("Expected patterns | output, got \"{}\"", input))?;
+        let mut entry = PackedEntry::default();
+        fill(&mut entry.patterns, patterns)?;
+        fill(&mut entry.output, output)?;
+        Ok(entry)
+    }
+}
+
+/// parse exactly `slots.len()` patterns into `slots`
+fn fill(slots: &mut [Segments], input: &str) -> Result<(), String> {
+    let mut patterns = input.split_whitespace();
+    for slot in slots.iter_mut() {
+        *slot = patterns
+            .next()
+            .ok_or_else(|| format!("Too few patterns in \"{}\"", input))?
+            .parse()?;
+    }
+    match patterns.next() {
+        Some(_) => Err(format!("Too many patterns in \"{}\"", input)),
+        None => Ok(()),
+    }
+}
+
+/// decode every entry into the matching slot of `values`, which must be as
+/// long as `entries`
+pub fn decode_batch(entries: &[PackedEntry], values: &mut [Option<u32>]) {
+    assert_eq!(entries.len(), values.len());
+    for (entry, value) in entries.iter().zip(values.iter_mut()) {
+        *value = entry.decode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn packed_input(filename: &str) -> Vec<PackedEntry> {
+        fs::read_to_string(filename)
+            .unwrap()
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let entry: PackedEntry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+                .parse()
+                .unwrap();
+        assert_eq!(entry.decode(), Some(5353));
+    }
+
+    #[test]
+    fn test_matches_solver() {
+        let entries = packed_input("input");
+        let mut values = vec![None; entries.len()];
+        decode_batch(&entries, &mut values);
+        for (entry, value) in crate::get_input("input").iter().zip(values) {
+            assert_eq!(value.map(|v| v as i32), entry.output_value().ok());
+        }
+    }
+
+    #[test]
+    fn test_many_entries() {
+        let entries = packed_input("input");
+        let many: Vec<PackedEntry> = entries
+            .iter()
+            .cycle()
+            .take(entries.len() * 500)
+            .copied()
+            .collect();
+        let mut values = vec![None; many.len()];
+        decode_batch(&many, &mut values);
+        let total: u32 = values.iter().map(|value| value.unwrap()).sum();
+        assert_eq!(total, 983030 * 500);
+    }
+
+    #[test]
+    fn test_undecodable() {
+        // no 4 to test against
+        let entry: PackedEntry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb cagedb ab ab | ab ab ab ab"
+                .parse()
+                .unwrap();
+        assert_eq!(entry.decode(), None);
+        assert!("ab | cd".parse::<PackedEntry>().is_err());
+        assert!("a b c d e f g h i j k | a b c d"
+            .parse::<PackedEntry>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_wiring() {
+        // abceg isn't a digit under any wiring, but the subset tests
+        // alone would call it a 2
+        let line =
+            "abcefg cf abceg acdfg bcdf abdfg abdefg acf abcdefg abcdfg | abceg cf acf abdfg";
+        let entry: PackedEntry = line.parse().unwrap();
+        assert_eq!(entry.decode(), None);
+        assert!(matches!(
+            crate::Entry::from(&entry).output_value(),
+            Err(crate::WiringError::Conflict(_))
+        ));
+    }
+}
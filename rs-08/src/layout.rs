@@ -0,0 +1,239 @@
+/// A kind of display: what its segments are called and which segments light
+/// up for each symbol it can show
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+    segments: Vec<char>,
+    /// each symbol and its segments, one bit per segment in `segments` order
+    symbols: Vec<(char, u32)>,
+}
+
+/// the puzzle's seven segments
+///
+/// ```text
+///  aaaa
+/// b    c
+/// b    c
+///  dddd
+/// e    f
+/// e    f
+///  gggg
+/// ```
+const SEVEN_SEGMENTS: &str = "abcdefg";
+
+pub(crate) const DECIMAL: [(char, &str); 10] = [
+    ('0', "abcefg"),
+    ('1', "cf"),
+    ('2', "acdeg"),
+    ('3', "acdfg"),
+    ('4', "bcdf"),
+    ('5', "abdfg"),
+    ('6', "abdefg"),
+    ('7', "acf"),
+    ('8', "abcdefg"),
+    ('9', "abcdfg"),
+];
+
+/// the letters past 9, with b and d in lower case so they aren't 8 and 0
+const HEX_LETTERS: [(char, &str); 6] = [
+    ('A', "abcdef"),
+    ('b', "bdefg"),
+    ('C', "abeg"),
+    ('d', "cdefg"),
+    ('E', "abdeg"),
+    ('F', "abde"),
+];
+
+/// fourteen segments, the outer six and the middle pair like a seven segment
+/// display (but with its own letters) plus a star of diagonals and verticals
+///
+/// ```text
+///  aaaaaaa
+/// f i j k b
+/// f  ijk  b
+///  ggg hhh
+/// e  lmn  c
+/// e l m n c
+///  ddddddd
+/// ```
+const FOURTEEN_SEGMENTS: &str = "abcdefghijklmn";
+
+const ALPHANUMERIC: [(char, &str); 36] = [
+    ('0', "abcdefkl"),
+    ('1', "bck"),
+    ('2', "abdegh"),
+    ('3', "abcdh"),
+    ('4', "bcfgh"),
+    ('5', "acdfgh"),
+    ('6', "acdefgh"),
+    ('7', "abc"),
+    ('8', "abcdefgh"),
+    ('9', "abcdfgh"),
+    ('A', "abcefgh"),
+    ('B', "abcdhjm"),
+    ('C', "adef"),
+    ('D', "abcdjm"),
+    ('E', "adefg"),
+    ('F', "aefg"),
+    ('G', "acdefh"),
+    ('H', "bcefgh"),
+    ('I', "adjm"),
+    ('J', "bcde"),
+    ('K', "efgkn"),
+    ('L', "def"),
+    ('M', "bcefik"),
+    ('N', "bcefin"),
+    ('O', "abcdef"),
+    ('P', "abefgh"),
+    ('Q', "abcdefn"),
+    ('R', "abefghn"),
+    ('S', "acdhi"),
+    ('T', "ajm"),
+    ('U', "bcdef"),
+    ('V', "efkl"),
+    ('W', "bcefln"),
+    ('X', "ikln"),
+    ('Y', "ikm"),
+    ('Z', "adkl"),
+];
+
+/// glyphs that light only one half of the split top or bottom on sixteen
+/// segments, without them the halves could never be told apart
+const SIXTEEN_SEGMENT_GLYPHS: [(char, &str); 2] = [('1', "adjmp"), ('J', "bclp")];
+
+impl Layout {
+    /// a layout from segment names and the segments each symbol lights,
+    /// every symbol needs its own pattern for the display to be readable
+    pub fn new(segments: &str, symbols: &[(char, &str)]) -> Result<Self, String> {
+        let segments: Vec<char> = segments.chars().collect();
+        if segments.len() > 32 {
+            return Err(format!("{} segments is more than 32", segments.len()));
+        }
+        let mut layout = Self {
+            segments,
+            symbols: vec![],
+        };
+        for &(symbol, lit) in symbols {
+            let mask = layout.mask(lit.chars()).ok_or_else(|| {
+                format!("Symbol {} lights an unknown segment in \"{}\"", symbol, lit)
+            })?;
+            if let Some((other, _)) = layout.symbols.iter().find(|(_, m)| *m == mask) {
+                return Err(format!("Symbols {} and {} look the same", other, symbol));
+            }
+            layout.symbols.push((symbol, mask));
+        }
+        Ok(layout)
+    }
+
+    /// the puzzle's display, 0 to 9 on seven segments
+    pub fn decimal() -> Self {
+        Self::new(SEVEN_SEGMENTS, &DECIMAL).unwrap()
+    }
+
+    /// 0 to F on seven segments
+    pub fn hexadecimal() -> Self {
+        let symbols: Vec<(char, &str)> =
+            DECIMAL.iter().chain(HEX_LETTERS.iter()).copied().collect();
+        Self::new(SEVEN_SEGMENTS, &symbols).unwrap()
+    }
+
+    /// 0 to 9 and A to Z on fourteen segments
+    pub fn fourteen_segment() -> Self {
+        Self::new(FOURTEEN_SEGMENTS, &ALPHANUMERIC).unwrap()
+    }
+
+    /// The fourteen segment display with its top and bottom split in two,
+    /// `o` and `p` being the right halves of `a` and `d`. It shows the same
+    /// symbols, mostly with both halves lit.
+    pub fn sixteen_segment() -> Self {
+        let glyphs: Vec<(char, String)> = ALPHANUMERIC
+            .iter()
+            .map(|&(symbol, lit)| {
+                let glyph = match SIXTEEN_SEGMENT_GLYPHS.iter().find(|(s, _)| *s == symbol) {
+                    Some((_, glyph)) => glyph.to_string(),
+                    None => lit.replace('a', "ao").replace('d', "dp"),
+                };
+                (symbol, glyph)
+            })
+            .collect();
+        let symbols: Vec<(char, &str)> = glyphs
+            .iter()
+            .map(|(symbol, lit)| (*symbol, lit.as_str()))
+            .collect();
+        Self::new(&format!("{}op", FOURTEEN_SEGMENTS), &symbols).unwrap()
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "decimal" => Ok(Self::decimal()),
+            "hex" => Ok(Self::hexadecimal()),
+            "14-segment" => Ok(Self::fourteen_segment()),
+            "16-segment" => Ok(Self::sixteen_segment()),
+            _ => Err(format!("Unknown display \"{}\"", name)),
+        }
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// the name of the segment (or wire) at `index`
+    pub fn segment(&self, index: usize) -> char {
+        self.segments[index]
+    }
+
+    pub fn symbols(&self) -> &[(char, u32)] {
+        &self.symbols
+    }
+
+    /// one bit per segment, `None` if any segment isn't on this display
+    pub fn mask(&self, lit: impl IntoIterator<Item = char>) -> Option<u32> {
+        lit.into_iter().try_fold(0, |mask, segment| {
+            let index = self.segments.iter().position(|&s| s == segment)?;
+            Some(mask | 1 << index)
+        })
+    }
+
+    /// the symbol showing exactly these segments
+    pub fn symbol(&self, mask: u32) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|(_, m)| *m == mask)
+            .map(|(symbol, _)| *symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal() {
+        let layout = Layout::decimal();
+        assert_eq!(
+            layout.symbol(layout.mask("acf".chars()).unwrap()),
+            Some('7')
+        );
+        assert_eq!(layout.symbol(layout.mask("ab".chars()).unwrap()), None);
+        assert_eq!(layout.mask("ax".chars()), None);
+    }
+
+    #[test]
+    fn test_built_in_layouts() {
+        assert_eq!(Layout::hexadecimal().symbols().len(), 16);
+        assert_eq!(Layout::fourteen_segment().segment_count(), 14);
+        let sixteen = Layout::sixteen_segment();
+        assert_eq!(sixteen.segment_count(), 16);
+        let i = sixteen.mask("aodpjm".chars()).unwrap();
+        assert_eq!(sixteen.symbol(i), Some('I'));
+        assert!(Layout::from_name("9-segment").is_err());
+    }
+
+    #[test]
+    fn test_invalid_layouts() {
+        assert_eq!(
+            Layout::new("abc", &[('x', "ab"), ('y', "ba")]),
+            Err("Symbols x and y look the same".to_string())
+        );
+        assert!(Layout::new("abc", &[('x', "abd")]).is_err());
+    }
+}
@@ -1,40 +1,109 @@
 #![allow(unused_attributes, dead_code)]
 
 use std::collections::HashSet;
-use std::fs;
+use std::{env, fs};
 
+use batch::PackedEntry;
+use layout::Layout;
+use segments::Segments;
+use wiring::WiringError;
+
+mod batch;
+mod layout;
+mod segments;
+mod wiring;
+
+/// `cargo run -- <display> <filename>` decodes every entry's output on
+/// another kind of display, one of decimal, hex, 14-segment or 16-segment
 fn main() {
     println!("part 1: {}", part_1("input"));
     println!("part 2: {}", part_2("input"));
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let [name, filename] = &args[..] {
+        let layout = Layout::from_name(name).unwrap();
+        let sets = |patterns: &str| -> Vec<HashSet<char>> {
+            patterns
+                .split_whitespace()
+                .map(|pattern| pattern.chars().collect())
+                .collect()
+        };
+        for (i, line) in fs::read_to_string(filename).unwrap().lines().enumerate() {
+            let (patterns, output) = line.split_once(" | ").unwrap();
+            match decode_output(&layout, &sets(patterns), &sets(output)) {
+                Ok(output) => println!("{}: {}", i + 1, output),
+                Err(error) => println!("{}: {}", i + 1, error),
+            }
+        }
+    }
 }
 
 fn part_1(filename: &str) -> i32 {
     let mut unique_length_count = 0;
     for entry in get_input(filename).iter() {
         for digit in entry.output_digits.iter() {
-            if let Some(_) = digit.value {
+            if digit.value.is_some() {
                 unique_length_count += 1;
             }
         }
     }
     unique_length_count
 }
+/// decodes with the packed batch decoder, falling back to the wiring solver
+/// for any entry it can't manage
 fn part_2(filename: &str) -> i32 {
-    get_input(filename)
-        .iter_mut()
-        .map(|entry| entry.deduce())
+    let packed = get_packed_input(filename);
+    let mut values = vec![None; packed.len()];
+    batch::decode_batch(&packed, &mut values);
+    packed
+        .iter()
+        .zip(values)
+        .map(|(entry, value)| match value {
+            Some(value) => value as i32,
+            None => Entry::from(entry).output_value().unwrap(),
+        })
         .sum()
 }
 
+/// Work out the wiring from every pattern on the display, the outputs
+/// included, then read off the output. Sets of letters work for any layout.
+fn decode_output(
+    layout: &Layout,
+    patterns: &[HashSet<char>],
+    output: &[HashSet<char>],
+) -> Result<String, WiringError> {
+    let all: Vec<&HashSet<char>> = patterns.iter().chain(output.iter()).collect();
+    let wiring = layout.solve(&all)?;
+    // every pattern decodes, or the wiring wouldn't have been found
+    Ok(output
+        .iter()
+        .map(|pattern| wiring.decode(pattern).unwrap())
+        .collect())
+}
+
 #[derive(Debug, Clone)]
 struct Digit {
-    segments: HashSet<char>,
+    segments: Segments,
     value: Option<usize>,
 }
 
 impl Digit {
-    fn set_value(&mut self, value: usize) {
-        self.value = Some(value);
+    /// digits with a length no other digit has (1, 4, 7 and 8) are known
+    /// straight away
+    fn new(segments: Segments) -> Self {
+        let value = match segments.len() {
+            2 => Some(1),
+            3 => Some(7),
+            4 => Some(4),
+            7 => Some(8),
+            _ => None,
+        };
+        Digit { segments, value }
+    }
+
+    /// the segments as a set of letters
+    fn to_set(&self) -> HashSet<char> {
+        self.segments.to_set()
     }
 }
 
@@ -46,119 +115,38 @@ struct Entry {
 }
 
 impl Entry {
-    fn deduce(self: &mut Self) -> i32 {
-        let s4 = self
-            .all_digits
-            .iter()
-            .find(|d| d.value == Some(4))
-            .unwrap()
-            .clone();
-        let s7 = self
-            .all_digits
-            .iter()
-            .find(|d| d.value == Some(7))
-            .unwrap()
-            .clone();
-
-        // find 9 (4 is a subset of 9, but 0 and 6 aren't)
-        let mut s9 = None;
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 6 && digit.segments.is_superset(&s4.segments) {
-                digit.set_value(9);
-                s9 = Some(digit.clone());
-            }
-        }
-
-        // find 0 (0 is a subset of 7, but 6 isn't)
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 6
-                && digit.segments.is_superset(&s7.segments)
-                && digit.value == None
-            {
-                digit.set_value(0);
-            }
-        }
-
-        // find 6 (it's the last remaining 6-segment digit)
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 6 && digit.value == None {
-                digit.set_value(6);
-            }
-        }
-
-        // find 3 (7 is a subset of 3, but 2 and 5 aren't)
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 5 && digit.segments.is_superset(&s7.segments) {
-                digit.set_value(3);
-            }
-        }
-
-        // find 5 (5 is a subset of 9, but 2 isn't)
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 5
-                && digit.segments.is_subset(&s9.as_ref().unwrap().segments)
-                && digit.value == None
-            {
-                digit.set_value(5);
-            }
-        }
+    /// the output as a number, solving for the wiring
+    fn output_value(&self) -> Result<i32, WiringError> {
+        let sets =
+            |digits: &[Digit]| -> Vec<HashSet<char>> { digits.iter().map(Digit::to_set).collect() };
+        let output = decode_output(
+            &Layout::decimal(),
+            &sets(&self.all_digits),
+            &sets(&self.output_digits),
+        )?;
+        Ok(output.parse().unwrap())
+    }
+}
 
-        // find 2 (it's the last remaining digit)
-        for digit in &mut self.all_digits.iter_mut() {
-            if digit.segments.len() == 5 && digit.value == None {
-                digit.set_value(2);
-            }
+impl From<&PackedEntry> for Entry {
+    fn from(entry: &PackedEntry) -> Self {
+        Entry {
+            all_digits: entry.patterns.iter().copied().map(Digit::new).collect(),
+            output_digits: entry.output.iter().copied().map(Digit::new).collect(),
+            is_all_known: true,
         }
-
-        // look up self.output_digits and return a number
-        let mut out = vec![];
-
-        self.output_digits.iter().for_each(|digit| {
-            self.all_digits.iter().for_each(|d| {
-                if d.segments == digit.segments {
-                    out.push(d.value.unwrap());
-                }
-            });
-        });
-
-        out.iter().rev().enumerate().fold(0, |sum, (i, x)| {
-            let multiplier = 10usize.pow(i as u32);
-            sum + multiplier as i32 * (*x as i32)
-        })
     }
 }
 
 fn get_input(filename: &str) -> Vec<Entry> {
+    get_packed_input(filename).iter().map(Entry::from).collect()
+}
+
+fn get_packed_input(filename: &str) -> Vec<PackedEntry> {
     fs::read_to_string(filename)
         .unwrap()
         .lines()
-        .map(|line| {
-            let (all_digits_str, output_str) = line.split_once(" | ").unwrap();
-            Entry {
-                all_digits: parse_digit(all_digits_str),
-                output_digits: parse_digit(output_str),
-                is_all_known: true,
-            }
-        })
-        .collect()
-}
-
-fn parse_digit(digit_str: &str) -> Vec<Digit> {
-    digit_str
-        .split_whitespace()
-        .map(|digit_str| {
-            let value = match digit_str.len() {
-                2 => Some(1),
-                4 => Some(4),
-                3 => Some(7),
-                7 => Some(8),
-                _ => None,
-            };
-            Digit {
-                segments: digit_str.chars().collect(),
-                value,
-            }
-        })
+        .map(|line| line.parse().unwrap())
         .collect()
 }
 
@@ -185,4 +173,44 @@ mod test {
     fn test_part_2_real() {
         assert_eq!(part_2("input"), 983030);
     }
+
+    fn parse_digit(digit_str: &str) -> Vec<Digit> {
+        digit_str
+            .split_whitespace()
+            .map(|digit_str| Digit::new(digit_str.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_digits() {
+        let digits = parse_digit("cf acf abdfg");
+        assert_eq!(
+            digits.iter().map(|digit| digit.value).collect::<Vec<_>>(),
+            vec![Some(1), Some(7), None]
+        );
+        assert!(digits[0].segments.is_subset(digits[1].segments));
+        assert_eq!(digits[1].to_set(), "acf".chars().collect());
+    }
+
+    #[test]
+    fn test_output_value_errors() {
+        let entry = Entry {
+            all_digits: parse_digit("ab cd"),
+            output_digits: parse_digit("ab"),
+            is_all_known: true,
+        };
+        assert_eq!(
+            entry.output_value(),
+            Err(WiringError::Conflict(vec!["cd".to_string()]))
+        );
+        let entry = Entry {
+            all_digits: parse_digit("ab abcdefg"),
+            output_digits: parse_digit("ab"),
+            is_all_known: true,
+        };
+        assert!(matches!(
+            entry.output_value(),
+            Err(WiringError::Ambiguous(_))
+        ));
+    }
 }
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// A pattern on the puzzle's seven segment display, one bit per segment
+/// with `a` lowest, so set operations are single instructions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Segments(u8);
+
+impl Segments {
+    pub const EMPTY: Segments = Segments(0);
+
+    /// a glyph from a table like `layout::DECIMAL`, failing to compile if it
+    /// has a segment past `g`
+    pub const fn from_letters(letters: &str) -> Segments {
+        let letters = letters.as_bytes();
+        let mut bits = 0;
+        let mut i = 0;
+        while i < letters.len() {
+            assert!(letters[i] >= b'a' && letters[i] <= b'g');
+            bits |= 1 << (letters[i] - b'a');
+            i += 1;
+        }
+        Segments(bits)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_subset(self, other: Segments) -> bool {
+        self.0 & other.0 == self.0
+    }
+
+    pub fn is_superset(self, other: Segments) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn intersection(self, other: Segments) -> Segments {
+        Segments(self.0 & other.0)
+    }
+
+    /// the same segments as a set of letters
+    pub fn to_set(self) -> HashSet<char> {
+        ('a'..='g')
+            .enumerate()
+            .filter(|(i, _)| self.0 & 1 << i != 0)
+            .map(|(_, segment)| segment)
+            .collect()
+    }
+
+    /// `None` if any segment isn't `a` to `g`
+    pub fn from_set(set: &HashSet<char>) -> Option<Self> {
+        set.iter().try_fold(Segments::EMPTY, |segments, &segment| {
+            Some(Segments(segments.0 | Self::bit(segment)?))
+        })
+    }
+
+    fn bit(segment: char) -> Option<u8> {
+        match segment {
+            'a'..='g' => Some(1 << (segment as u8 - b'a')),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Segments {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        input
+            .chars()
+            .try_fold(Segments::EMPTY, |segments, segment| {
+                let bit = Self::bit(segment)
+                    .ok_or_else(|| format!("Unknown segment {} in \"{}\"", segment, input))?;
+                Ok(Segments(segments.0 | bit))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_operations() {
+        let seven: Segments = "acf".parse().unwrap();
+        let three: Segments = "gfdca".parse().unwrap();
+        let one: Segments = "fc".parse().unwrap();
+        assert_eq!(seven.bits(), 0b0100101);
+        assert_eq!(Segments::from_letters("fac"), seven);
+        assert_eq!(three.len(), 5);
+        assert!(one.is_subset(seven) && seven.is_subset(three));
+        assert!(three.is_superset(seven) && !seven.is_superset(three));
+        assert_eq!(three.intersection("bcdf".parse().unwrap()).len(), 3);
+        assert!(Segments::EMPTY.is_empty());
+        assert!("abh".parse::<Segments>().is_err());
+    }
+
+    #[test]
+    fn test_hash_set_round_trip() {
+        let segments: Segments = "bdeg".parse().unwrap();
+        let set = segments.to_set();
+        assert_eq!(set, "gedb".chars().collect());
+        assert_eq!(Segments::from_set(&set), Some(segments));
+        assert_eq!(Segments::from_set(&"ax".chars().collect()), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Error, Formatter};
+
+use crate::layout::Layout;
+
+fn pattern_string(pattern: &HashSet<char>) -> String {
+    let mut chars: Vec<char> = pattern.iter().copied().collect();
+    chars.sort_unstable();
+    chars.into_iter().collect()
+}
+
+/// which segment each wire is really connected to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wiring<'a> {
+    layout: &'a Layout,
+    /// the segment's bit for each wire
+    segments: Vec<u32>,
+}
+
+fn rewire(segments: &[u32], wires: u32) -> u32 {
+    segments
+        .iter()
+        .enumerate()
+        .filter(|(wire, _)| wires & 1 << wire != 0)
+        .fold(0, |lit, (_, segment)| lit | segment)
+}
+
+impl Wiring<'_> {
+    /// the segment a wire drives
+    pub fn segment(&self, wire: char) -> Option<char> {
+        let index = (0..self.layout.segment_count()).find(|&i| self.layout.segment(i) == wire)?;
+        Some(
+            self.layout
+                .segment(self.segments[index].trailing_zeros() as usize),
+        )
+    }
+
+    /// the symbol a pattern of lit wires shows, if it's a symbol at all
+    pub fn decode(&self, pattern: &HashSet<char>) -> Option<char> {
+        let wires = self.layout.mask(pattern.iter().copied())?;
+        self.layout.symbol(rewire(&self.segments, wires))
+    }
+}
+
+/// why there isn't exactly one wiring
+#[derive(Clone, Debug, PartialEq)]
+pub enum WiringError {
+    /// no wiring works, these patterns can't show a symbol alongside the
+    /// ones before them
+    Conflict(Vec<String>),
+    /// more than one wiring works, these are the wires that aren't pinned
+    /// down along with every segment they could be driving
+    Ambiguous(Vec<String>),
+}
+
+impl Display for WiringError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            WiringError::Conflict(patterns) => {
+                write!(f, "No wiring shows a symbol for {}", patterns.join(", "))
+            }
+            WiringError::Ambiguous(wires) => {
+                write!(f, "More than one wiring works, {}", wires.join(", "))
+            }
+        }
+    }
+}
+
+/// A search through the ways the wires could be connected, assigning a
+/// segment to one wire at a time
+struct Search<'a> {
+    layout: &'a Layout,
+    patterns: &'a [u32],
+    /// a wire that has to drive a particular segment
+    fixed: Option<(usize, u32)>,
+    /// stop once this many wirings are found
+    limit: usize,
+    found: Vec<Vec<u32>>,
+}
+
+impl Search<'_> {
+    /// Whether the wires assigned so far could still make every pattern a
+    /// symbol. Each pattern needs a symbol with as many segments where the
+    /// assigned wires light exactly the segments they drive.
+    fn consistent(&self, segments: &[u32]) -> bool {
+        let assigned = ((1u64 << segments.len()) - 1) as u32;
+        let reachable = segments.iter().fold(0, |all, s| all | s);
+        self.patterns.iter().all(|&pattern| {
+            let lit = rewire(segments, pattern & assigned);
+            self.layout.symbols().iter().any(|&(_, symbol)| {
+                symbol.count_ones() == pattern.count_ones() && symbol & reachable == lit
+            })
+        })
+    }
+
+    fn run(&mut self, segments: &mut Vec<u32>) {
+        if self.found.len() >= self.limit {
+            return;
+        }
+        let wire = segments.len();
+        if wire == self.layout.segment_count() {
+            self.found.push(segments.clone());
+            return;
+        }
+        let used = segments.iter().fold(0, |all, s| all | s);
+        for segment in (0..self.layout.segment_count()).map(|i| 1u32 << i) {
+            if used & segment != 0 {
+                continue;
+            }
+            if matches!(self.fixed, Some((w, s)) if w == wire && s != segment) {
+                continue;
+            }
+            segments.push(segment);
+            if self.consistent(segments) {
+                self.run(segments);
+            }
+            segments.pop();
+        }
+    }
+}
+
+impl Layout {
+    fn search(&self, patterns: &[u32], fixed: Option<(usize, u32)>, limit: usize) -> Vec<Vec<u32>> {
+        let mut search = Search {
+            layout: self,
+            patterns,
+            fixed,
+            limit,
+            found: vec![],
+        };
+        search.run(&mut vec![]);
+        search.found
+    }
+
+    /// Find the one wiring where every pattern shows a symbol, cutting off
+    /// any partial wiring as soon as a pattern can't work.
+    pub fn solve(&self, patterns: &[&HashSet<char>]) -> Result<Wiring<'_>, WiringError> {
+        let masks: Vec<Option<u32>> = patterns
+            .iter()
+            .map(|pattern| self.mask(pattern.iter().copied()))
+            .collect();
+        let mut found = match masks.iter().copied().collect::<Option<Vec<u32>>>() {
+            Some(masks) => self.search(&masks, None, 2),
+            None => vec![],
+        };
+        match found.len() {
+            1 => Ok(Wiring {
+                layout: self,
+                segments: found.remove(0),
+            }),
+            0 => Err(WiringError::Conflict(self.conflicts(patterns, &masks))),
+            _ => Err(WiringError::Ambiguous(self.ambiguities(&masks))),
+        }
+    }
+
+    /// add the patterns one at a time, keeping those that still leave a
+    /// wiring and listing the others
+    fn conflicts(&self, patterns: &[&HashSet<char>], masks: &[Option<u32>]) -> Vec<String> {
+        let mut kept = vec![];
+        let mut conflicts: Vec<String> = vec![];
+        for (pattern, mask) in patterns.iter().zip(masks) {
+            if let Some(mask) = mask {
+                kept.push(*mask);
+                if !self.search(&kept, None, 1).is_empty() {
+                    continue;
+                }
+                kept.pop();
+            }
+            let pattern = pattern_string(pattern);
+            if !conflicts.contains(&pattern) {
+                conflicts.push(pattern);
+            }
+        }
+        conflicts
+    }
+
+    /// every segment each wire could drive, for the wires with a choice
+    fn ambiguities(&self, masks: &[Option<u32>]) -> Vec<String> {
+        let masks: Vec<u32> = masks.iter().flatten().copied().collect();
+        (0..self.segment_count())
+            .filter_map(|wire| {
+                let options: Vec<String> = (0..self.segment_count())
+                    .filter(|&segment| {
+                        !self
+                            .search(&masks, Some((wire, 1 << segment)), 1)
+                            .is_empty()
+                    })
+                    .map(|segment| self.segment(segment).to_string())
+                    .collect();
+                (options.len() > 1)
+                    .then(|| format!("{} -> {}", self.segment(wire), options.join("|")))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(input: &str) -> Vec<HashSet<char>> {
+        input
+            .split_whitespace()
+            .map(|pattern| pattern.chars().collect())
+            .collect()
+    }
+
+    fn solve_str(layout: &Layout, input: &str) -> Result<String, WiringError> {
+        let patterns = patterns(input);
+        let wiring = layout.solve(&patterns.iter().collect::<Vec<_>>())?;
+        Ok((0..layout.segment_count())
+            .map(|wire| wiring.segment(layout.segment(wire)).unwrap())
+            .collect())
+    }
+
+    /// every symbol then `output`, shown through wires connected in reverse
+    fn scrambled(layout: &Layout, output: &str) -> (Vec<HashSet<char>>, Vec<HashSet<char>>) {
+        let count = layout.segment_count();
+        let show = |mask: u32| -> HashSet<char> {
+            (0..count)
+                .filter(|segment| mask & 1 << segment != 0)
+                .map(|segment| layout.segment(count - 1 - segment))
+                .collect()
+        };
+        let all = layout
+            .symbols()
+            .iter()
+            .map(|&(_, mask)| show(mask))
+            .collect();
+        let shown = output
+            .chars()
+            .map(|symbol| {
+                let &(_, mask) = layout.symbols().iter().find(|(s, _)| *s == symbol).unwrap();
+                show(mask)
+            })
+            .collect();
+        (all, shown)
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let layout = Layout::decimal();
+        let input =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab cdfeb fcadb cdfeb cdbaf";
+        // d is the top, e top left, a top right, f middle and so on
+        assert_eq!(solve_str(&layout, input), Ok("cfgabde".to_string()));
+        let patterns = patterns(input);
+        let wiring = layout.solve(&patterns.iter().collect::<Vec<_>>()).unwrap();
+        let output: String = patterns[10..]
+            .iter()
+            .map(|pattern| wiring.decode(pattern).unwrap())
+            .collect();
+        assert_eq!(output, "5353");
+    }
+
+    #[test]
+    fn test_without_the_easy_digits() {
+        let layout = Layout::decimal();
+        // no 1, 4 or 8 for the old deduction to start from
+        let patterns = patterns("cefabd cdfgeb cagedb cdfbe gcdfa fbcad dab");
+        let wiring = layout.solve(&patterns.iter().collect::<Vec<_>>()).unwrap();
+        assert_eq!(wiring.decode(&"ab".chars().collect()), Some('1'));
+        // without the 7 either, every digit left lights both the top and the
+        // bottom, so nothing can tell their wires apart
+        assert_eq!(
+            solve_str(&layout, "cefabd cdfgeb cagedb cdfbe gcdfa fbcad"),
+            Err(WiringError::Ambiguous(vec![
+                "c -> a|g".to_string(),
+                "d -> a|g".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_ambiguous() {
+        let layout = Layout::decimal();
+        assert_eq!(
+            solve_str(&layout, "acedgfb abcdefg"),
+            Err(WiringError::Ambiguous(vec![
+                "a -> a|b|c|d|e|f|g".to_string(),
+                "b -> a|b|c|d|e|f|g".to_string(),
+                "c -> a|b|c|d|e|f|g".to_string(),
+                "d -> a|b|c|d|e|f|g".to_string(),
+                "e -> a|b|c|d|e|f|g".to_string(),
+                "f -> a|b|c|d|e|f|g".to_string(),
+                "g -> a|b|c|d|e|f|g".to_string(),
+            ]))
+        );
+        // a 1, 7 and 8 only pin down the top segment
+        let error = solve_str(&layout, "cf acf abcdefg").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "More than one wiring works, b -> b|d|e|g, c -> c|f, d -> b|d|e|g, \
+             e -> b|d|e|g, f -> c|f, g -> b|d|e|g"
+        );
+    }
+
+    #[test]
+    fn test_conflict() {
+        let layout = Layout::decimal();
+        // two different patterns can't both be a 1
+        let error = solve_str(&layout, "ab cd").unwrap_err();
+        assert!(matches!(error, WiringError::Conflict(ref patterns) if patterns.len() == 1));
+        assert_eq!(
+            solve_str(&layout, "ab xy"),
+            Err(WiringError::Conflict(vec!["xy".to_string()]))
+        );
+        assert_eq!(
+            solve_str(&layout, "abcdefgh").unwrap_err().to_string(),
+            "No wiring shows a symbol for abcdefgh"
+        );
+    }
+
+    #[test]
+    fn test_conflict_listed_once() {
+        let layout = Layout::decimal();
+        // a conflicting pattern shown twice is only listed the first time
+        assert_eq!(
+            solve_str(&layout, "ab cd ab cd"),
+            Err(WiringError::Conflict(vec!["cd".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_other_layouts() {
+        for (layout, output) in [
+            (Layout::hexadecimal(), "C0FFEE"),
+            (Layout::fourteen_segment(), "HELLO2021"),
+            (Layout::sixteen_segment(), "JUST1MORE"),
+        ] {
+            let (all, shown) = scrambled(&layout, output);
+            let patterns: Vec<&HashSet<char>> = all.iter().chain(shown.iter()).collect();
+            let wiring = layout.solve(&patterns).unwrap();
+            let decoded: String = shown
+                .iter()
+                .map(|pattern| wiring.decode(pattern).unwrap())
+                .collect();
+            assert_eq!(decoded, output);
+        }
+    }
+}